@@ -1,30 +1,463 @@
 //! Automated market maker pool management contract.
 //!
-//! This module provides a minimal stub of the AMM pool manager contract used by the
-//! Synnergy network. In the full system, opcodes are dispatched via the Go-based
-//! `opcode_dispatcher.go` with gas costs defined in `gas_table.go`. This Rust version
-//! focuses on basic validation and structure to ensure compile-time safety.
+//! Implements a genuine constant-product (`x*y=k`) AMM, modelled on Uniswap v2: pools hold
+//! two reserves, liquidity providers mint/burn pool shares, and swaps pay a 0.3% fee. Reserve
+//! products overflow `u64`, so the invariant math runs on [`U256`], a minimal 256-bit unsigned
+//! integer. `execute_opcode` remains the dispatcher: it rejects a call with zero gas and routes
+//! the opcode to the matching pool operation; no per-operation gas cost is charged.
 
-/// Core AMM pool manager type.
+use std::collections::HashMap;
+use std::fmt;
+use std::ops::{Add, Div, Mul};
+
+/// Minimal 256-bit unsigned integer, stored as four big-endian `u64` limbs.
+///
+/// Only the operations the AMM math needs are implemented (wrapping add/mul, checked sub, long division
+/// and an integer square root); this is not a general-purpose bignum library.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord)]
+pub struct U256([u64; 4]);
+
+impl U256 {
+    pub const ZERO: U256 = U256([0; 4]);
+
+    /// Builds a `U256` from a `u64` value.
+    pub fn from_u64(v: u64) -> Self {
+        U256([0, 0, 0, v])
+    }
+
+    /// Returns the value truncated to `u64`, discarding any higher bits.
+    pub fn low_u64(&self) -> u64 {
+        self.0[3]
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.0 == [0; 4]
+    }
+
+    fn bit(&self, n: u32) -> bool {
+        let limb = 3 - (n / 64) as usize;
+        (self.0[limb] >> (n % 64)) & 1 == 1
+    }
+
+    fn set_bit(&mut self, n: u32) {
+        let limb = 3 - (n / 64) as usize;
+        self.0[limb] |= 1 << (n % 64);
+    }
+
+    fn shl1(self) -> Self {
+        let mut r = [0u64; 4];
+        let mut carry = 0u64;
+        for i in (0..4).rev() {
+            r[i] = (self.0[i] << 1) | carry;
+            carry = self.0[i] >> 63;
+        }
+        U256(r)
+    }
+
+    /// Checked subtraction; `None` on underflow.
+    pub fn checked_sub(self, other: Self) -> Option<Self> {
+        if self < other {
+            return None;
+        }
+        let mut r = [0u64; 4];
+        let mut borrow = 0i128;
+        for i in (0..4).rev() {
+            let diff = self.0[i] as i128 - other.0[i] as i128 - borrow;
+            if diff < 0 {
+                r[i] = (diff + (1i128 << 64)) as u64;
+                borrow = 1;
+            } else {
+                r[i] = diff as u64;
+                borrow = 0;
+            }
+        }
+        Some(U256(r))
+    }
+
+    /// Long division, returning `(quotient, remainder)`. Panics on division by zero.
+    pub fn div_rem(self, divisor: Self) -> (Self, Self) {
+        assert!(!divisor.is_zero(), "division by zero");
+        let mut quotient = U256::ZERO;
+        let mut remainder = U256::ZERO;
+        for i in (0..256u32).rev() {
+            remainder = remainder.shl1();
+            if self.bit(i) {
+                remainder.0[3] |= 1;
+            }
+            if remainder >= divisor {
+                remainder = remainder.checked_sub(divisor).unwrap();
+                quotient.set_bit(i);
+            }
+        }
+        (quotient, remainder)
+    }
+
+    /// Integer square root via binary search (`floor(sqrt(self))`).
+    pub fn sqrt(self) -> Self {
+        if self.is_zero() {
+            return U256::ZERO;
+        }
+        let mut lo = U256::ZERO;
+        let mut hi = self;
+        while lo < hi {
+            let mid = (lo + hi + U256::from_u64(1)) / U256::from_u64(2);
+            if mid * mid <= self {
+                lo = mid;
+            } else {
+                hi = mid.checked_sub(U256::from_u64(1)).unwrap();
+            }
+        }
+        lo
+    }
+
+    pub fn min(self, other: Self) -> Self {
+        if self <= other {
+            self
+        } else {
+            other
+        }
+    }
+}
+
+impl Add for U256 {
+    type Output = Self;
+
+    /// Wrapping addition.
+    fn add(self, other: Self) -> Self {
+        let mut r = [0u64; 4];
+        let mut carry = 0u128;
+        for i in (0..4).rev() {
+            let sum = self.0[i] as u128 + other.0[i] as u128 + carry;
+            r[i] = sum as u64;
+            carry = sum >> 64;
+        }
+        U256(r)
+    }
+}
+
+impl Mul for U256 {
+    type Output = Self;
+
+    /// Wrapping multiplication (truncates any overflow past 256 bits).
+    fn mul(self, other: Self) -> Self {
+        let mut r = [0u64; 4];
+        for i in (0..4).rev() {
+            let mut carry = 0u128;
+            for j in (0..4).rev() {
+                let pos = (3 - i) + (3 - j);
+                if pos > 3 {
+                    break;
+                }
+                let k = 3 - pos;
+                let t = r[k] as u128 + self.0[i] as u128 * other.0[j] as u128 + carry;
+                r[k] = t as u64;
+                carry = t >> 64;
+            }
+        }
+        U256(r)
+    }
+}
+
+impl Div for U256 {
+    type Output = Self;
+
+    /// Long division; panics on division by zero (see [`U256::div_rem`]).
+    fn div(self, divisor: Self) -> Self {
+        self.div_rem(divisor).0
+    }
+}
+
+impl From<u64> for U256 {
+    fn from(v: u64) -> Self {
+        U256::from_u64(v)
+    }
+}
+
+impl fmt::Display for U256 {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.low_u64())
+    }
+}
+
+/// Errors returned by pool operations.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AmmError {
+    /// A pool for this token pair already exists.
+    PoolExists,
+    /// No pool exists for this token pair.
+    PoolNotFound,
+    /// The pool does not hold enough reserves/shares to satisfy the request.
+    InsufficientLiquidity,
+    /// A swap would have produced less than the caller's minimum acceptable output.
+    SlippageExceeded,
+    /// The caller provided zero gas.
+    InsufficientGas,
+    /// The opcode does not map to a known pool operation.
+    UnknownOpcode,
+}
+
+impl fmt::Display for AmmError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AmmError::PoolExists => write!(f, "pool already exists"),
+            AmmError::PoolNotFound => write!(f, "pool not found"),
+            AmmError::InsufficientLiquidity => write!(f, "insufficient liquidity"),
+            AmmError::SlippageExceeded => write!(f, "slippage exceeded"),
+            AmmError::InsufficientGas => write!(f, "insufficient gas"),
+            AmmError::UnknownOpcode => write!(f, "unknown opcode"),
+        }
+    }
+}
+
+impl std::error::Error for AmmError {}
+
+/// Reserves and outstanding LP shares for a single token pair.
+#[derive(Clone, Debug)]
+pub struct Pool {
+    pub reserve_a: U256,
+    pub reserve_b: U256,
+    pub total_shares: U256,
+}
+
+/// An opcode paired with the operands it needs. The Go-side dispatcher is responsible for
+/// decoding wire bytes into this shape before calling [`AmmPoolManager::execute_opcode`].
+pub enum AmmOp {
+    CreatePool {
+        token_a: String,
+        token_b: String,
+    },
+    AddLiquidity {
+        token_a: String,
+        token_b: String,
+        owner: String,
+        dx: u64,
+        dy: u64,
+    },
+    RemoveLiquidity {
+        token_a: String,
+        token_b: String,
+        owner: String,
+        lp_amount: U256,
+    },
+    Swap {
+        token_a: String,
+        token_b: String,
+        amount_in: u64,
+        token_in_is_a: bool,
+        min_amount_out: u64,
+    },
+}
+
+pub const OP_CREATE_POOL: u8 = 0;
+pub const OP_ADD_LIQUIDITY: u8 = 1;
+pub const OP_REMOVE_LIQUIDITY: u8 = 2;
+pub const OP_SWAP: u8 = 3;
+
+/// Manages constant-product liquidity pools keyed by an unordered token pair.
 #[derive(Default)]
-pub struct AmmPoolManager;
+pub struct AmmPoolManager {
+    pools: HashMap<(String, String), Pool>,
+    lp_balances: HashMap<(String, String, String), U256>,
+}
+
+fn pool_key(token_a: &str, token_b: &str) -> (String, String) {
+    if token_a <= token_b {
+        (token_a.to_string(), token_b.to_string())
+    } else {
+        (token_b.to_string(), token_a.to_string())
+    }
+}
 
 impl AmmPoolManager {
-    /// Creates a new [`AmmPoolManager`].
+    /// Creates a new, empty [`AmmPoolManager`].
     pub fn new() -> Self {
         Self::default()
     }
 
-    /// Executes a generic opcode. Returns an error if provided gas is zero.
-    pub fn execute_opcode(&self, opcode: u8, gas: u64) -> Result<(), String> {
-        if gas == 0 {
-            return Err("insufficient gas".into());
+    /// Creates an empty pool for `token_a`/`token_b`.
+    pub fn create_pool(&mut self, token_a: &str, token_b: &str) -> Result<(), AmmError> {
+        let key = pool_key(token_a, token_b);
+        if self.pools.contains_key(&key) {
+            return Err(AmmError::PoolExists);
         }
-        // In production this would interface with opcode_dispatcher.go
-        // to execute the opcode using gas_table.go for gas calculations.
-        let _ = opcode; // placeholder usage
+        self.pools.insert(
+            key,
+            Pool {
+                reserve_a: U256::ZERO,
+                reserve_b: U256::ZERO,
+                total_shares: U256::ZERO,
+            },
+        );
         Ok(())
     }
+
+    /// Deposits `dx`/`dy` into the pool and mints LP shares for `owner`.
+    ///
+    /// The first deposit mints `sqrt(dx*dy)` shares; subsequent deposits mint
+    /// `min(dx*supply/reserve_a, dy*supply/reserve_b)` to keep the pool's price unchanged.
+    pub fn add_liquidity(
+        &mut self,
+        token_a: &str,
+        token_b: &str,
+        owner: &str,
+        dx: u64,
+        dy: u64,
+    ) -> Result<U256, AmmError> {
+        let key = pool_key(token_a, token_b);
+        let pool = self.pools.get_mut(&key).ok_or(AmmError::PoolNotFound)?;
+        let dx = U256::from_u64(dx);
+        let dy = U256::from_u64(dy);
+        let minted = if pool.total_shares.is_zero() {
+            (dx * dy).sqrt()
+        } else {
+            let shares_a = dx * pool.total_shares / pool.reserve_a;
+            let shares_b = dy * pool.total_shares / pool.reserve_b;
+            shares_a.min(shares_b)
+        };
+        if minted.is_zero() {
+            return Err(AmmError::InsufficientLiquidity);
+        }
+        pool.reserve_a = pool.reserve_a + dx;
+        pool.reserve_b = pool.reserve_b + dy;
+        pool.total_shares = pool.total_shares + minted;
+        let balance = self
+            .lp_balances
+            .entry((key.0, key.1, owner.to_string()))
+            .or_insert(U256::ZERO);
+        *balance = *balance + minted;
+        Ok(minted)
+    }
+
+    /// Burns `lp_amount` shares held by `owner`, returning the pro-rata `(amount_a, amount_b)`.
+    pub fn remove_liquidity(
+        &mut self,
+        token_a: &str,
+        token_b: &str,
+        owner: &str,
+        lp_amount: U256,
+    ) -> Result<(u64, u64), AmmError> {
+        let key = pool_key(token_a, token_b);
+        let balance_key = (key.0.clone(), key.1.clone(), owner.to_string());
+        let balance = self
+            .lp_balances
+            .get(&balance_key)
+            .copied()
+            .unwrap_or(U256::ZERO);
+        if lp_amount.is_zero() || balance < lp_amount {
+            return Err(AmmError::InsufficientLiquidity);
+        }
+        let pool = self.pools.get_mut(&key).ok_or(AmmError::PoolNotFound)?;
+        let amount_a = lp_amount * pool.reserve_a / pool.total_shares;
+        let amount_b = lp_amount * pool.reserve_b / pool.total_shares;
+        pool.reserve_a = pool
+            .reserve_a
+            .checked_sub(amount_a)
+            .ok_or(AmmError::InsufficientLiquidity)?;
+        pool.reserve_b = pool
+            .reserve_b
+            .checked_sub(amount_b)
+            .ok_or(AmmError::InsufficientLiquidity)?;
+        pool.total_shares = pool
+            .total_shares
+            .checked_sub(lp_amount)
+            .ok_or(AmmError::InsufficientLiquidity)?;
+        *self.lp_balances.get_mut(&balance_key).unwrap() =
+            balance.checked_sub(lp_amount).unwrap();
+        Ok((amount_a.low_u64(), amount_b.low_u64()))
+    }
+
+    /// Swaps `amount_in` of one side of the pool for the other, applying the 0.3% swap fee.
+    ///
+    /// `token_in_is_a` selects which reserve `amount_in` is added to. Fails with
+    /// [`AmmError::SlippageExceeded`] if the resulting output is below `min_amount_out`.
+    pub fn swap(
+        &mut self,
+        token_a: &str,
+        token_b: &str,
+        amount_in: u64,
+        token_in_is_a: bool,
+        min_amount_out: u64,
+    ) -> Result<u64, AmmError> {
+        let key = pool_key(token_a, token_b);
+        let pool = self.pools.get_mut(&key).ok_or(AmmError::PoolNotFound)?;
+        let (reserve_in, reserve_out) = if token_in_is_a {
+            (pool.reserve_a, pool.reserve_b)
+        } else {
+            (pool.reserve_b, pool.reserve_a)
+        };
+        if reserve_in.is_zero() || reserve_out.is_zero() {
+            return Err(AmmError::InsufficientLiquidity);
+        }
+        let amount_in = U256::from_u64(amount_in);
+        let amount_in_with_fee = amount_in * U256::from_u64(997);
+        let numerator = reserve_out * amount_in_with_fee;
+        let denominator = reserve_in * U256::from_u64(1000) + amount_in_with_fee;
+        let amount_out = numerator / denominator;
+        if amount_out.low_u64() < min_amount_out {
+            return Err(AmmError::SlippageExceeded);
+        }
+        let new_reserve_in = reserve_in + amount_in;
+        let new_reserve_out = reserve_out
+            .checked_sub(amount_out)
+            .ok_or(AmmError::InsufficientLiquidity)?;
+        if token_in_is_a {
+            pool.reserve_a = new_reserve_in;
+            pool.reserve_b = new_reserve_out;
+        } else {
+            pool.reserve_b = new_reserve_in;
+            pool.reserve_a = new_reserve_out;
+        }
+        Ok(amount_out.low_u64())
+    }
+
+    /// Dispatches `op` through the pool engine. A call with zero `gas` is rejected; `gas` is
+    /// not decremented or returned, so no per-operation cost is charged.
+    pub fn execute_opcode(&mut self, opcode: u8, gas: u64, op: AmmOp) -> Result<(), AmmError> {
+        if gas == 0 {
+            return Err(AmmError::InsufficientGas);
+        }
+        match (opcode, op) {
+            (OP_CREATE_POOL, AmmOp::CreatePool { token_a, token_b }) => {
+                self.create_pool(&token_a, &token_b)
+            }
+            (
+                OP_ADD_LIQUIDITY,
+                AmmOp::AddLiquidity {
+                    token_a,
+                    token_b,
+                    owner,
+                    dx,
+                    dy,
+                },
+            ) => self
+                .add_liquidity(&token_a, &token_b, &owner, dx, dy)
+                .map(|_| ()),
+            (
+                OP_REMOVE_LIQUIDITY,
+                AmmOp::RemoveLiquidity {
+                    token_a,
+                    token_b,
+                    owner,
+                    lp_amount,
+                },
+            ) => self
+                .remove_liquidity(&token_a, &token_b, &owner, lp_amount)
+                .map(|_| ()),
+            (
+                OP_SWAP,
+                AmmOp::Swap {
+                    token_a,
+                    token_b,
+                    amount_in,
+                    token_in_is_a,
+                    min_amount_out,
+                },
+            ) => self
+                .swap(&token_a, &token_b, amount_in, token_in_is_a, min_amount_out)
+                .map(|_| ()),
+            _ => Err(AmmError::UnknownOpcode),
+        }
+    }
 }
 
 #[cfg(test)]
@@ -32,14 +465,67 @@ mod tests {
     use super::*;
 
     #[test]
-    fn new_creates_manager() {
-        let mgr = AmmPoolManager::new();
-        assert!(mgr.execute_opcode(0, 1).is_ok());
+    fn u256_basic_arithmetic() {
+        let a = U256::from_u64(1_000_000);
+        let b = U256::from_u64(3);
+        assert_eq!((a + b).low_u64(), 1_000_003);
+        assert_eq!((a * b).low_u64(), 3_000_000);
+        assert_eq!((a / b).low_u64(), 333_333);
+        assert_eq!(U256::from_u64(144).sqrt().low_u64(), 12);
+    }
+
+    #[test]
+    fn create_pool_rejects_duplicates() {
+        let mut mgr = AmmPoolManager::new();
+        assert!(mgr.create_pool("A", "B").is_ok());
+        assert_eq!(mgr.create_pool("B", "A"), Err(AmmError::PoolExists));
+    }
+
+    #[test]
+    fn first_deposit_mints_sqrt_invariant() {
+        let mut mgr = AmmPoolManager::new();
+        mgr.create_pool("A", "B").unwrap();
+        let minted = mgr.add_liquidity("A", "B", "alice", 100, 400).unwrap();
+        assert_eq!(minted.low_u64(), 200); // sqrt(100*400) == 200
+    }
+
+    #[test]
+    fn swap_applies_fee_and_respects_slippage() {
+        let mut mgr = AmmPoolManager::new();
+        mgr.create_pool("A", "B").unwrap();
+        mgr.add_liquidity("A", "B", "alice", 1_000, 1_000).unwrap();
+        let out = mgr.swap("A", "B", 100, true, 1).unwrap();
+        assert!(out < 100); // fee + slippage means strictly less than a 1:1 swap
+        assert_eq!(
+            mgr.swap("A", "B", 100, true, out + 1),
+            Err(AmmError::SlippageExceeded)
+        );
+    }
+
+    #[test]
+    fn remove_liquidity_returns_pro_rata_share() {
+        let mut mgr = AmmPoolManager::new();
+        mgr.create_pool("A", "B").unwrap();
+        let minted = mgr.add_liquidity("A", "B", "alice", 100, 400).unwrap();
+        let (amount_a, amount_b) = mgr.remove_liquidity("A", "B", "alice", minted).unwrap();
+        assert_eq!((amount_a, amount_b), (100, 400));
     }
 
     #[test]
-    fn zero_gas_fails() {
-        let mgr = AmmPoolManager::new();
-        assert!(mgr.execute_opcode(0, 0).is_err());
+    fn execute_opcode_rejects_zero_gas_and_unknown_opcodes() {
+        let mut mgr = AmmPoolManager::new();
+        let op = AmmOp::CreatePool {
+            token_a: "A".into(),
+            token_b: "B".into(),
+        };
+        assert_eq!(
+            mgr.execute_opcode(OP_CREATE_POOL, 0, op),
+            Err(AmmError::InsufficientGas)
+        );
+        let op = AmmOp::CreatePool {
+            token_a: "A".into(),
+            token_b: "B".into(),
+        };
+        assert_eq!(mgr.execute_opcode(255, 1, op), Err(AmmError::UnknownOpcode));
     }
 }
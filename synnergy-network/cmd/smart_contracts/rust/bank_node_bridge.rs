@@ -1,40 +1,68 @@
-//! Bank node bridge contract stub.
+//! Bank node bridge contract.
 //!
-//! In the complete Synnergy network this contract enables secure communication between
-//! bank nodes. Opcode dispatching and gas metering are handled externally in Go. This file
-//! offers a lightweight Rust version to guarantee compilation and provide basic gas
-//! validation.
+//! Enables secure communication between bank nodes. Opcode execution and gas management run
+//! through the shared stack-based [`vm::Vm`] rather than a no-op gas check, so this contract
+//! gets real arithmetic, stack and keyed-store opcodes for free.
 
-#[derive(Default)]
-pub struct BankNodeBridge;
+mod vm;
+
+use vm::storage::Storage;
+use vm::Vm;
+
+pub struct BankNodeBridge {
+    vm: Vm,
+}
+
+impl Default for BankNodeBridge {
+    fn default() -> Self {
+        Self { vm: Vm::new(0) }
+    }
+}
 
 impl BankNodeBridge {
     pub fn new() -> Self {
         Self::default()
     }
 
-    pub fn execute_opcode(&self, opcode: u8, gas: u64) -> Result<(), String> {
-        if gas == 0 {
-            return Err("insufficient gas".into());
-        }
-        let _ = opcode;
-        Ok(())
+    /// Decodes and executes `(opcode, operand)` against the contract's VM, debiting `gas` and
+    /// persisting any `Load`/`Store` opcode through `storage`.
+    pub fn execute_opcode(
+        &mut self,
+        opcode: u8,
+        operand: u64,
+        gas: u64,
+        storage: &mut dyn Storage,
+    ) -> Result<(), String> {
+        self.vm.gas_remaining = gas;
+        self.vm
+            .execute_single(opcode, operand, storage)
+            .map_err(|e| e.to_string())
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use vm::storage::InMemoryStorage;
 
     #[test]
     fn bridge_with_gas() {
-        let bridge = BankNodeBridge::new();
-        assert!(bridge.execute_opcode(2, 5).is_ok());
+        let mut bridge = BankNodeBridge::new();
+        let mut storage = InMemoryStorage::new();
+        assert!(bridge.execute_opcode(vm::OP_PUSH, 2, 5, &mut storage).is_ok());
     }
 
     #[test]
     fn bridge_without_gas() {
-        let bridge = BankNodeBridge::new();
-        assert!(bridge.execute_opcode(2, 0).is_err());
+        let mut bridge = BankNodeBridge::new();
+        let mut storage = InMemoryStorage::new();
+        assert!(bridge.execute_opcode(vm::OP_PUSH, 2, 0, &mut storage).is_err());
+    }
+
+    #[test]
+    fn bridge_rejects_unknown_opcode() {
+        let mut bridge = BankNodeBridge::new();
+        let mut storage = InMemoryStorage::new();
+        assert!(bridge.execute_opcode(0xFF, 0, 10, &mut storage).is_err());
     }
 }
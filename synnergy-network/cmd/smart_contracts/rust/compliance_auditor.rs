@@ -1,39 +1,68 @@
-//! Compliance auditor contract stub.
+//! Compliance auditor contract.
 //!
-//! Represents a simplified version of the compliance auditor. The comprehensive logic for
-//! opcode handling and gas management is implemented in Go. This Rust code ensures that
-//! basic checks exist and that the module compiles correctly.
+//! Represents a simplified version of the compliance auditor. Opcode execution and gas
+//! management run through the shared stack-based [`vm::Vm`] rather than a no-op gas check,
+//! so this contract gets real arithmetic, stack and keyed-store opcodes for free.
 
-#[derive(Default)]
-pub struct ComplianceAuditor;
+mod vm;
+
+use vm::storage::Storage;
+use vm::Vm;
+
+pub struct ComplianceAuditor {
+    vm: Vm,
+}
+
+impl Default for ComplianceAuditor {
+    fn default() -> Self {
+        Self { vm: Vm::new(0) }
+    }
+}
 
 impl ComplianceAuditor {
     pub fn new() -> Self {
         Self::default()
     }
 
-    pub fn execute_opcode(&self, opcode: u8, gas: u64) -> Result<(), String> {
-        if gas == 0 {
-            return Err("insufficient gas".into());
-        }
-        let _ = opcode;
-        Ok(())
+    /// Decodes and executes `(opcode, operand)` against the contract's VM, debiting `gas` and
+    /// persisting any `Load`/`Store` opcode through `storage`.
+    pub fn execute_opcode(
+        &mut self,
+        opcode: u8,
+        operand: u64,
+        gas: u64,
+        storage: &mut dyn Storage,
+    ) -> Result<(), String> {
+        self.vm.gas_remaining = gas;
+        self.vm
+            .execute_single(opcode, operand, storage)
+            .map_err(|e| e.to_string())
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use vm::storage::InMemoryStorage;
 
     #[test]
     fn auditor_runs_with_gas() {
-        let auditor = ComplianceAuditor::new();
-        assert!(auditor.execute_opcode(4, 7).is_ok());
+        let mut auditor = ComplianceAuditor::new();
+        let mut storage = InMemoryStorage::new();
+        assert!(auditor.execute_opcode(vm::OP_PUSH, 4, 7, &mut storage).is_ok());
     }
 
     #[test]
     fn auditor_rejects_no_gas() {
-        let auditor = ComplianceAuditor::new();
-        assert!(auditor.execute_opcode(4, 0).is_err());
+        let mut auditor = ComplianceAuditor::new();
+        let mut storage = InMemoryStorage::new();
+        assert!(auditor.execute_opcode(vm::OP_PUSH, 4, 0, &mut storage).is_err());
+    }
+
+    #[test]
+    fn auditor_rejects_unknown_opcode() {
+        let mut auditor = ComplianceAuditor::new();
+        let mut storage = InMemoryStorage::new();
+        assert!(auditor.execute_opcode(0xFF, 0, 10, &mut storage).is_err());
     }
 }
@@ -1,23 +1,216 @@
-//! Consensus tester contract stub.
+//! Consensus tester contract: a real two-phase PBFT voting round.
 //!
-//! Used to simulate consensus operations within the Synnergy ecosystem. The real
-//! implementation relies on Go for opcode execution and gas handling. This Rust file adds
-//! structural checks and unit tests.
+//! Models the classic `n = 3f+1` Byzantine-fault-tolerant flow: validators `PREPARE` on a
+//! proposed block digest, and once `2f+1` matching prepares arrive the round moves to
+//! `COMMIT`, finalizing when `2f+1` commits on that same digest are seen. `f` is derived from
+//! [`FaultToleranceChecker`]'s configured fault fraction. `execute_opcode` stays the
+//! dispatcher that routes opcodes into the round.
 
-#[derive(Default)]
-pub struct ConsensusTester;
+mod fault_tolerance_checker;
+
+use fault_tolerance_checker::FaultToleranceChecker;
+use std::collections::{HashMap, HashSet};
+
+/// A block digest identifying the value being voted on.
+pub type Digest = String;
+
+/// Errors returned while driving a PBFT round.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ConsensusError {
+    /// No proposal is active for the current view/sequence.
+    NoActiveProposal,
+    /// Equivocation (two different digests from one validator in one phase) has pushed the
+    /// faulty-validator count past the tolerated threshold.
+    TooManyFaultyValidators,
+    /// A commit vote was recorded before the prepare phase reached quorum.
+    NotYetPrepared,
+}
+
+fn cast_vote(
+    tally: &mut HashMap<String, Digest>,
+    equivocators: &mut HashSet<String>,
+    validator: &str,
+    digest: &str,
+) {
+    match tally.get(validator) {
+        Some(existing) if existing != digest => {
+            equivocators.insert(validator.to_string());
+        }
+        _ => {
+            tally.insert(validator.to_string(), digest.to_string());
+        }
+    }
+}
+
+fn quorum_digest(
+    tally: &HashMap<String, Digest>,
+    equivocators: &HashSet<String>,
+    quorum: usize,
+) -> Option<Digest> {
+    let mut counts: HashMap<&str, usize> = HashMap::new();
+    for (validator, digest) in tally {
+        if equivocators.contains(validator) {
+            continue;
+        }
+        *counts.entry(digest.as_str()).or_insert(0) += 1;
+    }
+    counts
+        .into_iter()
+        .find(|(_, count)| *count >= quorum)
+        .map(|(digest, _)| digest.to_string())
+}
+
+/// Opcodes dispatched through [`ConsensusTester::execute_opcode`].
+pub enum ConsensusOp {
+    Propose {
+        view: u64,
+        seq: u64,
+        digest: Digest,
+    },
+    Prepare {
+        validator: String,
+        digest: Digest,
+    },
+    Commit {
+        validator: String,
+        digest: Digest,
+    },
+    ViewChange {
+        new_view: u64,
+    },
+}
+
+pub const OP_PROPOSE: u8 = 0;
+pub const OP_PREPARE: u8 = 1;
+pub const OP_COMMIT: u8 = 2;
+pub const OP_VIEW_CHANGE: u8 = 3;
+
+/// Drives a single PBFT round across a fixed validator set.
+pub struct ConsensusTester {
+    checker: FaultToleranceChecker,
+    total_validators: usize,
+    view: u64,
+    seq: u64,
+    proposal: Option<Digest>,
+    prepares: HashMap<String, Digest>,
+    commits: HashMap<String, Digest>,
+    equivocators: HashSet<String>,
+}
 
 impl ConsensusTester {
-    pub fn new() -> Self {
-        Self::default()
+    /// Creates a tester for `total_validators` validators, tolerating the faulty fraction
+    /// configured on `fault_fraction` (e.g. `0.33` for classic `n = 3f+1` PBFT).
+    pub fn new(total_validators: usize, fault_fraction: f64) -> Self {
+        Self {
+            checker: FaultToleranceChecker::new(fault_fraction),
+            total_validators,
+            view: 0,
+            seq: 0,
+            proposal: None,
+            prepares: HashMap::new(),
+            commits: HashMap::new(),
+            equivocators: HashSet::new(),
+        }
+    }
+
+    /// The current view number.
+    pub fn view(&self) -> u64 {
+        self.view
+    }
+
+    /// The current sequence number.
+    pub fn seq(&self) -> u64 {
+        self.seq
+    }
+
+    fn quorum(&self) -> usize {
+        2 * self.checker.max_faulty(self.total_validators) + 1
     }
 
-    pub fn execute_opcode(&self, opcode: u8, gas: u64) -> Result<(), String> {
+    fn check_fault_budget(&self) -> Result<(), ConsensusError> {
+        if self
+            .checker
+            .is_tolerated(self.equivocators.len(), self.total_validators)
+        {
+            Ok(())
+        } else {
+            Err(ConsensusError::TooManyFaultyValidators)
+        }
+    }
+
+    /// Proposes `digest` for `view`/`seq`, clearing any prior round's vote tallies.
+    pub fn propose(&mut self, view: u64, seq: u64, digest: impl Into<Digest>) {
+        self.view = view;
+        self.seq = seq;
+        self.proposal = Some(digest.into());
+        self.prepares.clear();
+        self.commits.clear();
+        self.equivocators.clear();
+    }
+
+    /// Records a `PREPARE` vote from `validator`.
+    pub fn record_prepare(&mut self, validator: &str, digest: &str) -> Result<(), ConsensusError> {
+        if self.proposal.is_none() {
+            return Err(ConsensusError::NoActiveProposal);
+        }
+        cast_vote(&mut self.prepares, &mut self.equivocators, validator, digest);
+        self.check_fault_budget()
+    }
+
+    /// Records a `COMMIT` vote from `validator`. Rejected until the prepare phase has
+    /// reached quorum.
+    pub fn record_commit(&mut self, validator: &str, digest: &str) -> Result<(), ConsensusError> {
+        if self.prepared().is_none() {
+            return Err(ConsensusError::NotYetPrepared);
+        }
+        cast_vote(&mut self.commits, &mut self.equivocators, validator, digest);
+        self.check_fault_budget()
+    }
+
+    /// Returns the digest locked by a `2f+1` prepare quorum, if any.
+    pub fn prepared(&self) -> Option<Digest> {
+        quorum_digest(&self.prepares, &self.equivocators, self.quorum())
+    }
+
+    /// Returns the digest finalized by a `2f+1` commit quorum matching the locked prepare
+    /// digest, if any.
+    pub fn finalized(&self) -> Option<Digest> {
+        let locked = self.prepared()?;
+        let committed = quorum_digest(&self.commits, &self.equivocators, self.quorum())?;
+        (committed == locked).then_some(committed)
+    }
+
+    /// Signals a view-change timeout: bumps the view and resets all vote tallies.
+    pub fn view_change(&mut self, new_view: u64) {
+        self.view = new_view;
+        self.proposal = None;
+        self.prepares.clear();
+        self.commits.clear();
+        self.equivocators.clear();
+    }
+
+    /// Dispatches `op` through the PBFT round, rejecting a call with zero `gas`.
+    pub fn execute_opcode(&mut self, opcode: u8, gas: u64, op: ConsensusOp) -> Result<(), String> {
         if gas == 0 {
             return Err("insufficient gas".into());
         }
-        let _ = opcode;
-        Ok(())
+        match (opcode, op) {
+            (OP_PROPOSE, ConsensusOp::Propose { view, seq, digest }) => {
+                self.propose(view, seq, digest);
+                Ok(())
+            }
+            (OP_PREPARE, ConsensusOp::Prepare { validator, digest }) => self
+                .record_prepare(&validator, &digest)
+                .map_err(|e| format!("{e:?}")),
+            (OP_COMMIT, ConsensusOp::Commit { validator, digest }) => self
+                .record_commit(&validator, &digest)
+                .map_err(|e| format!("{e:?}")),
+            (OP_VIEW_CHANGE, ConsensusOp::ViewChange { new_view }) => {
+                self.view_change(new_view);
+                Ok(())
+            }
+            _ => Err("unknown opcode".into()),
+        }
     }
 }
 
@@ -25,15 +218,77 @@ impl ConsensusTester {
 mod tests {
     use super::*;
 
+    fn round() -> ConsensusTester {
+        // n = 3f+1 = 4 validators, f = 1.
+        ConsensusTester::new(4, 0.33)
+    }
+
+    #[test]
+    fn finalizes_once_prepare_and_commit_quorums_match() {
+        let mut r = round();
+        r.propose(1, 1, "block-a");
+        for v in ["v1", "v2", "v3"] {
+            r.record_prepare(v, "block-a").unwrap();
+        }
+        assert_eq!(r.prepared(), Some("block-a".to_string()));
+        assert_eq!(r.finalized(), None);
+
+        for v in ["v1", "v2", "v3"] {
+            r.record_commit(v, "block-a").unwrap();
+        }
+        assert_eq!(r.finalized(), Some("block-a".to_string()));
+    }
+
+    #[test]
+    fn commit_before_prepare_quorum_is_rejected() {
+        let mut r = round();
+        r.propose(1, 1, "block-a");
+        assert_eq!(
+            r.record_commit("v1", "block-a"),
+            Err(ConsensusError::NotYetPrepared)
+        );
+    }
+
+    #[test]
+    fn equivocation_beyond_threshold_is_rejected() {
+        let mut r = round();
+        r.propose(1, 1, "block-a");
+        r.record_prepare("v1", "block-a").unwrap();
+        // v1 equivocates: same phase, different digest. With f = 1 this single
+        // equivocator is still tolerated...
+        assert!(r.record_prepare("v1", "block-b").is_ok());
+        // ...but a second equivocator pushes the faulty count past f = 1.
+        r.record_prepare("v2", "block-a").unwrap();
+        assert_eq!(
+            r.record_prepare("v2", "block-c"),
+            Err(ConsensusError::TooManyFaultyValidators)
+        );
+    }
+
     #[test]
-    fn consensus_with_gas() {
-        let tester = ConsensusTester::new();
-        assert!(tester.execute_opcode(5, 3).is_ok());
+    fn view_change_resets_tallies() {
+        let mut r = round();
+        r.propose(1, 1, "block-a");
+        r.record_prepare("v1", "block-a").unwrap();
+        r.view_change(2);
+        assert_eq!(r.prepared(), None);
+        assert_eq!(
+            r.record_prepare("v1", "block-a"),
+            Err(ConsensusError::NoActiveProposal)
+        );
     }
 
     #[test]
-    fn consensus_without_gas() {
-        let tester = ConsensusTester::new();
-        assert!(tester.execute_opcode(5, 0).is_err());
+    fn execute_opcode_rejects_zero_gas() {
+        let mut r = round();
+        let op = ConsensusOp::Propose {
+            view: 1,
+            seq: 1,
+            digest: "block-a".into(),
+        };
+        assert_eq!(
+            r.execute_opcode(OP_PROPOSE, 0, op),
+            Err("insufficient gas".into())
+        );
     }
 }
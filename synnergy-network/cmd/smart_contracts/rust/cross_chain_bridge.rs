@@ -1,39 +1,256 @@
-//! Cross-chain bridge contract stub.
+//! Cross-chain bridge contract: SPV-verified lock-and-mint transfers.
 //!
-//! Facilitates asset transfers between chains in the Synnergy network. The complete
-//! execution logic resides within Go-based opcode dispatchers and gas tables. This Rust
-//! version includes basic gas validation and unit tests for structural assurance.
+//! For trust-minimized transfers the bridge must verify that a deposit event really
+//! occurred on the source chain before releasing funds, i.e. simplified payment
+//! verification. [`CrossChainBridge::register_header`] stores a trusted transaction root
+//! per remote chain/height; [`CrossChainBridge::verify_deposit`] recomputes the Merkle root
+//! from a leaf and its sibling proof and checks it against that root; [`CrossChainBridge::claim`]
+//! only unlocks the wrapped asset once per verified leaf, tracking spent leaves to prevent
+//! replay.
 
+mod keccak;
+
+use keccak::keccak256;
+use std::collections::{HashMap, HashSet};
+
+fn node_hash(left: &[u8; 32], right: &[u8; 32]) -> [u8; 32] {
+    let mut buf = [0u8; 64];
+    buf[..32].copy_from_slice(left);
+    buf[32..].copy_from_slice(right);
+    keccak256(&buf)
+}
+
+/// Errors returned by bridge operations.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BridgeError {
+    /// No trusted header has been registered for this chain/height.
+    UnknownHeader,
+    /// The supplied Merkle proof does not fold up to the registered transaction root.
+    InvalidProof,
+    /// This leaf has already been claimed.
+    AlreadyClaimed,
+    /// The caller provided zero gas.
+    InsufficientGas,
+    /// The opcode does not map to a known bridge operation.
+    UnknownOpcode,
+}
+
+/// Opcodes dispatched through [`CrossChainBridge::execute_opcode`].
+pub enum BridgeOp {
+    RegisterHeader {
+        chain_id: u64,
+        block_height: u64,
+        tx_root: [u8; 32],
+    },
+    Claim {
+        chain_id: u64,
+        block_height: u64,
+        leaf: [u8; 32],
+        proof: Vec<[u8; 32]>,
+        index: u64,
+    },
+}
+
+pub const OP_REGISTER_HEADER: u8 = 0;
+pub const OP_CLAIM: u8 = 1;
+
+/// Tracks trusted remote-chain headers and claimed deposits.
 #[derive(Default)]
-pub struct CrossChainBridge;
+pub struct CrossChainBridge {
+    headers: HashMap<(u64, u64), [u8; 32]>,
+    spent_leaves: HashSet<(u64, [u8; 32])>,
+}
 
 impl CrossChainBridge {
     pub fn new() -> Self {
         Self::default()
     }
 
-    pub fn execute_opcode(&self, opcode: u8, gas: u64) -> Result<(), String> {
-        if gas == 0 {
-            return Err("insufficient gas".into());
+    /// Registers `tx_root` as the trusted transaction Merkle root for `chain_id` at
+    /// `block_height`.
+    pub fn register_header(&mut self, chain_id: u64, block_height: u64, tx_root: [u8; 32]) {
+        self.headers.insert((chain_id, block_height), tx_root);
+    }
+
+    /// Recomputes the Merkle root from `leaf` and `proof`, folding `keccak256(current ||
+    /// sibling)` or `keccak256(sibling || current)` according to the bit of `index` at each
+    /// level, and checks it against the header registered for `chain_id`/`height`.
+    pub fn verify_deposit(
+        &self,
+        chain_id: u64,
+        height: u64,
+        leaf: [u8; 32],
+        proof: &[[u8; 32]],
+        index: u64,
+    ) -> bool {
+        let Some(&root) = self.headers.get(&(chain_id, height)) else {
+            return false;
+        };
+        let mut hash = leaf;
+        for (level, sibling) in proof.iter().enumerate() {
+            hash = if (index >> level) & 1 == 0 {
+                node_hash(&hash, sibling)
+            } else {
+                node_hash(sibling, &hash)
+            };
+        }
+        hash == root
+    }
+
+    /// Verifies the deposit and, on success, unlocks/mints the wrapped asset for `leaf`
+    /// exactly once. Subsequent claims for the same `(chain_id, leaf)` fail with
+    /// [`BridgeError::AlreadyClaimed`].
+    pub fn claim(
+        &mut self,
+        chain_id: u64,
+        height: u64,
+        leaf: [u8; 32],
+        proof: &[[u8; 32]],
+        index: u64,
+    ) -> Result<(), BridgeError> {
+        if !self.headers.contains_key(&(chain_id, height)) {
+            return Err(BridgeError::UnknownHeader);
+        }
+        if self.spent_leaves.contains(&(chain_id, leaf)) {
+            return Err(BridgeError::AlreadyClaimed);
+        }
+        if !self.verify_deposit(chain_id, height, leaf, proof, index) {
+            return Err(BridgeError::InvalidProof);
         }
-        let _ = opcode;
+        self.spent_leaves.insert((chain_id, leaf));
         Ok(())
     }
+
+    /// Dispatches `op` through the bridge, rejecting a call with zero `gas`.
+    pub fn execute_opcode(&mut self, opcode: u8, gas: u64, op: BridgeOp) -> Result<(), BridgeError> {
+        if gas == 0 {
+            return Err(BridgeError::InsufficientGas);
+        }
+        match (opcode, op) {
+            (
+                OP_REGISTER_HEADER,
+                BridgeOp::RegisterHeader {
+                    chain_id,
+                    block_height,
+                    tx_root,
+                },
+            ) => {
+                self.register_header(chain_id, block_height, tx_root);
+                Ok(())
+            }
+            (
+                OP_CLAIM,
+                BridgeOp::Claim {
+                    chain_id,
+                    block_height,
+                    leaf,
+                    proof,
+                    index,
+                },
+            ) => self.claim(chain_id, block_height, leaf, &proof, index),
+            _ => Err(BridgeError::UnknownOpcode),
+        }
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    /// Builds a 4-leaf Merkle tree over `leaves` and returns `(root, proof_for(index))`.
+    fn tree_proof(leaves: &[[u8; 32]], index: usize) -> ([u8; 32], Vec<[u8; 32]>) {
+        assert_eq!(leaves.len(), 4, "fixture assumes exactly 4 leaves");
+        let level0 = leaves.to_vec();
+        let level1 = [
+            node_hash(&level0[0], &level0[1]),
+            node_hash(&level0[2], &level0[3]),
+        ];
+        let root = node_hash(&level1[0], &level1[1]);
+
+        let proof = match index {
+            0 => vec![level0[1], level1[1]],
+            1 => vec![level0[0], level1[1]],
+            2 => vec![level0[3], level1[0]],
+            3 => vec![level0[2], level1[0]],
+            _ => unreachable!(),
+        };
+        (root, proof)
+    }
+
+    #[test]
+    fn verify_deposit_accepts_valid_proof_and_rejects_tampering() {
+        let leaves = [
+            keccak256(b"deposit-0"),
+            keccak256(b"deposit-1"),
+            keccak256(b"deposit-2"),
+            keccak256(b"deposit-3"),
+        ];
+        let (root, proof) = tree_proof(&leaves, 2);
+
+        let mut bridge = CrossChainBridge::new();
+        bridge.register_header(1, 100, root);
+
+        assert!(bridge.verify_deposit(1, 100, leaves[2], &proof, 2));
+        assert!(!bridge.verify_deposit(1, 100, leaves[1], &proof, 2));
+        assert!(!bridge.verify_deposit(1, 999, leaves[2], &proof, 2));
+    }
+
     #[test]
-    fn bridge_runs_with_gas() {
-        let bridge = CrossChainBridge::new();
-        assert!(bridge.execute_opcode(6, 8).is_ok());
+    fn claim_is_rejected_without_a_registered_header() {
+        let leaves = [
+            keccak256(b"deposit-0"),
+            keccak256(b"deposit-1"),
+            keccak256(b"deposit-2"),
+            keccak256(b"deposit-3"),
+        ];
+        let (_, proof) = tree_proof(&leaves, 0);
+        let mut bridge = CrossChainBridge::new();
+        assert_eq!(
+            bridge.claim(1, 100, leaves[0], &proof, 0),
+            Err(BridgeError::UnknownHeader)
+        );
+    }
+
+    #[test]
+    fn claim_succeeds_once_then_rejects_replay() {
+        let leaves = [
+            keccak256(b"deposit-0"),
+            keccak256(b"deposit-1"),
+            keccak256(b"deposit-2"),
+            keccak256(b"deposit-3"),
+        ];
+        let (root, proof) = tree_proof(&leaves, 0);
+
+        let mut bridge = CrossChainBridge::new();
+        bridge.register_header(1, 100, root);
+
+        assert!(bridge.claim(1, 100, leaves[0], &proof, 0).is_ok());
+        assert_eq!(
+            bridge.claim(1, 100, leaves[0], &proof, 0),
+            Err(BridgeError::AlreadyClaimed)
+        );
     }
 
     #[test]
-    fn bridge_fails_without_gas() {
-        let bridge = CrossChainBridge::new();
-        assert!(bridge.execute_opcode(6, 0).is_err());
+    fn execute_opcode_rejects_zero_gas_and_unknown_opcodes() {
+        let mut bridge = CrossChainBridge::new();
+        let op = BridgeOp::RegisterHeader {
+            chain_id: 1,
+            block_height: 1,
+            tx_root: [0u8; 32],
+        };
+        assert_eq!(
+            bridge.execute_opcode(OP_REGISTER_HEADER, 0, op),
+            Err(BridgeError::InsufficientGas)
+        );
+        let op = BridgeOp::RegisterHeader {
+            chain_id: 1,
+            block_height: 1,
+            tx_root: [0u8; 32],
+        };
+        assert_eq!(
+            bridge.execute_opcode(255, 1, op),
+            Err(BridgeError::UnknownOpcode)
+        );
     }
 }
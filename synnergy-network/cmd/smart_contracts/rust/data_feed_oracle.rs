@@ -1,39 +1,140 @@
-//! Data feed oracle contract stub.
+//! Data feed oracle contract.
 //!
-//! Supplies external data to the Synnergy network. The comprehensive opcode and gas logic is
-//! maintained in Go, while this Rust implementation guarantees basic validation and
-//! successful compilation.
+//! Supplies external data to the Synnergy network. Opcode execution and gas metering run
+//! through the shared stack-based [`vm::Vm`] rather than a no-op gas check, so this contract
+//! gets real arithmetic, stack and keyed-store opcodes for free. Submitted observations are
+//! accumulated into a [`merkle::MerkleTree`] so a verifier can challenge a single reported
+//! value against the committed root without trusting the whole batch.
 
-#[derive(Default)]
-pub struct DataFeedOracle;
+mod merkle;
+mod vm;
+
+use merkle::MerkleTree;
+use vm::storage::Storage;
+use vm::Vm;
+
+pub struct DataFeedOracle {
+    vm: Vm,
+    observations: Vec<Vec<u8>>,
+}
+
+impl Default for DataFeedOracle {
+    fn default() -> Self {
+        Self {
+            vm: Vm::new(0),
+            observations: Vec::new(),
+        }
+    }
+}
 
 impl DataFeedOracle {
     pub fn new() -> Self {
         Self::default()
     }
 
-    pub fn execute_opcode(&self, opcode: u8, gas: u64) -> Result<(), String> {
-        if gas == 0 {
-            return Err("insufficient gas".into());
-        }
-        let _ = opcode;
-        Ok(())
+    /// Records a new observation pending commitment.
+    pub fn submit(&mut self, data: impl Into<Vec<u8>>) {
+        self.observations.push(data.into());
+    }
+
+    fn tree(&self) -> MerkleTree {
+        let leaves = self
+            .observations
+            .iter()
+            .map(|o| merkle::hash_leaf(o))
+            .collect();
+        MerkleTree::new(leaves)
+    }
+
+    /// Commits to every observation submitted so far and returns the batch's Merkle root.
+    pub fn commit(&self) -> [u8; 32] {
+        self.tree().root()
+    }
+
+    /// Builds an inclusion proof for the observation at `index` against [`Self::commit`]'s
+    /// root. Returns `None` if `index` is out of bounds.
+    pub fn prove(&self, index: usize) -> Option<Vec<([u8; 32], bool)>> {
+        self.tree().proof(index)
+    }
+
+    /// Decodes and executes `(opcode, operand)` against the contract's VM, debiting `gas` and
+    /// persisting any `Load`/`Store` opcode through `storage`.
+    pub fn execute_opcode(
+        &mut self,
+        opcode: u8,
+        operand: u64,
+        gas: u64,
+        storage: &mut dyn Storage,
+    ) -> Result<(), String> {
+        self.vm.gas_remaining = gas;
+        self.vm
+            .execute_single(opcode, operand, storage)
+            .map_err(|e| e.to_string())
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use vm::storage::InMemoryStorage;
 
     #[test]
     fn oracle_executes_with_gas() {
-        let oracle = DataFeedOracle::new();
-        assert!(oracle.execute_opcode(8, 9).is_ok());
+        let mut oracle = DataFeedOracle::new();
+        let mut storage = InMemoryStorage::new();
+        assert!(oracle.execute_opcode(vm::OP_PUSH, 9, 9, &mut storage).is_ok());
     }
 
     #[test]
     fn oracle_rejects_zero_gas() {
-        let oracle = DataFeedOracle::new();
-        assert!(oracle.execute_opcode(8, 0).is_err());
+        let mut oracle = DataFeedOracle::new();
+        let mut storage = InMemoryStorage::new();
+        assert!(oracle.execute_opcode(vm::OP_PUSH, 9, 0, &mut storage).is_err());
+    }
+
+    #[test]
+    fn oracle_rejects_unknown_opcode() {
+        let mut oracle = DataFeedOracle::new();
+        let mut storage = InMemoryStorage::new();
+        assert!(oracle.execute_opcode(0xFF, 0, 10, &mut storage).is_err());
+    }
+
+    #[test]
+    fn oracle_persists_store_opcode_through_the_shared_storage_handle() {
+        let mut oracle = DataFeedOracle::new();
+        let mut storage = InMemoryStorage::new();
+        oracle
+            .execute_opcode(vm::OP_PUSH, 42, 10, &mut storage)
+            .unwrap();
+        oracle
+            .execute_opcode(vm::OP_STORE, 1, 10, &mut storage)
+            .unwrap();
+        assert_eq!(storage.get(&1u64.to_be_bytes()), Some(42u64.to_be_bytes().to_vec()));
+    }
+
+    #[test]
+    fn commit_root_changes_as_observations_are_submitted() {
+        let mut oracle = DataFeedOracle::new();
+        let empty_root = oracle.commit();
+        oracle.submit(b"eth_usd=3200".to_vec());
+        assert_ne!(oracle.commit(), empty_root);
+    }
+
+    #[test]
+    fn prove_verifies_a_submitted_observation() {
+        let mut oracle = DataFeedOracle::new();
+        oracle.submit(b"eth_usd=3200".to_vec());
+        oracle.submit(b"btc_usd=65000".to_vec());
+        let root = oracle.commit();
+        let proof = oracle.prove(1).expect("index in bounds");
+        let leaf = merkle::hash_leaf(b"btc_usd=65000");
+        assert!(merkle::verify(root, leaf, &proof));
+    }
+
+    #[test]
+    fn prove_returns_none_for_out_of_bounds_index() {
+        let mut oracle = DataFeedOracle::new();
+        oracle.submit(b"eth_usd=3200".to_vec());
+        assert!(oracle.prove(1).is_none());
     }
 }
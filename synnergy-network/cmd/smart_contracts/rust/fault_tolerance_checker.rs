@@ -27,6 +27,12 @@ impl FaultToleranceChecker {
         }
         (faulty_nodes as f64) <= self.threshold * (total_nodes as f64)
     }
+
+    /// Returns the maximum number of faulty validators (`f`) tolerated out of
+    /// `total_nodes`, derived from the configured threshold fraction.
+    pub fn max_faulty(&self, total_nodes: usize) -> usize {
+        (self.threshold * total_nodes as f64).floor() as usize
+    }
 }
 
 #[cfg(test)]
@@ -39,4 +45,11 @@ mod tests {
         assert!(checker.is_tolerated(1, 4));
         assert!(!checker.is_tolerated(2, 4));
     }
+
+    #[test]
+    fn max_faulty_derives_f_from_threshold() {
+        let checker = FaultToleranceChecker::new(0.33);
+        assert_eq!(checker.max_faulty(4), 1);
+        assert_eq!(checker.max_faulty(7), 2);
+    }
 }
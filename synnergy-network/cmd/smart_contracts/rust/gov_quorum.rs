@@ -1,25 +1,121 @@
-//! Governance quorum utility.
+//! Governance finality gadget: a stake-weighted two-phase BFT round.
+//!
+//! A flat vote-count percentage ignores validator weight and has no finality semantics.
+//! Borrowing the prevote/precommit structure from Tendermint-style BFT, [`BftRound`] tracks a
+//! validator set (`validator_id -> stake`) and collects two phases of votes on a value: a
+//! value is *locked* once prevotes covering more than two-thirds of total stake name it, and
+//! *finalized* once precommits covering more than two-thirds of total stake name that same
+//! locked value. A validator that votes for two different values within one phase equivocates
+//! and its second vote is rejected.
 
-/// Determines whether a vote meets a required quorum.
-pub struct GovQuorum {
-    required_percentage: f64,
+use std::collections::HashMap;
+
+/// Errors returned while recording a vote.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum GovQuorumError {
+    /// The voter is not part of this round's validator set.
+    UnknownValidator,
+    /// The validator already voted for a different value in this phase.
+    DoubleVote,
+}
+
+/// A single round of stake-weighted prevote/precommit voting on a value.
+pub struct BftRound {
+    validators: HashMap<String, u64>,
+    total_stake: u64,
+    prevotes: HashMap<String, String>,
+    precommits: HashMap<String, String>,
 }
 
-impl GovQuorum {
-    /// Creates a new quorum with a required percentage between 0.0 and 1.0.
-    pub fn new(required_percentage: f64) -> Self {
-        assert!((0.0..=1.0).contains(&required_percentage));
+impl BftRound {
+    /// Creates a round over `validators` mapping each validator id to its stake.
+    pub fn new(validators: HashMap<String, u64>) -> Self {
+        let total_stake = validators.values().sum();
         Self {
-            required_percentage,
+            validators,
+            total_stake,
+            prevotes: HashMap::new(),
+            precommits: HashMap::new(),
         }
     }
 
-    /// Returns `true` if the ratio of `votes_for` to `total_votes` meets the requirement.
-    pub fn has_quorum(&self, votes_for: u64, total_votes: u64) -> bool {
-        if total_votes == 0 {
-            return false;
+    /// `true` if `stake` is strictly more than two-thirds of this round's total stake.
+    fn exceeds_two_thirds(&self, stake: u64) -> bool {
+        stake.saturating_mul(3) > self.total_stake.saturating_mul(2)
+    }
+
+    fn record_vote(
+        votes: &mut HashMap<String, String>,
+        validators: &HashMap<String, u64>,
+        validator: &str,
+        value: String,
+    ) -> Result<(), GovQuorumError> {
+        if !validators.contains_key(validator) {
+            return Err(GovQuorumError::UnknownValidator);
+        }
+        match votes.get(validator) {
+            Some(existing) if *existing != value => Err(GovQuorumError::DoubleVote),
+            _ => {
+                votes.insert(validator.to_string(), value);
+                Ok(())
+            }
         }
-        (votes_for as f64) / (total_votes as f64) >= self.required_percentage
+    }
+
+    /// Records a prevote from `validator` for `value`.
+    pub fn record_prevote(
+        &mut self,
+        validator: &str,
+        value: impl Into<String>,
+    ) -> Result<(), GovQuorumError> {
+        Self::record_vote(&mut self.prevotes, &self.validators, validator, value.into())
+    }
+
+    /// Records a precommit from `validator` for `value`.
+    pub fn record_precommit(
+        &mut self,
+        validator: &str,
+        value: impl Into<String>,
+    ) -> Result<(), GovQuorumError> {
+        Self::record_vote(&mut self.precommits, &self.validators, validator, value.into())
+    }
+
+    /// Returns the stake-weighted leader of `votes`, if any value exceeds two-thirds of total
+    /// stake.
+    fn leading_value(&self, votes: &HashMap<String, String>) -> Option<String> {
+        let mut totals: HashMap<&str, u64> = HashMap::new();
+        for (validator, value) in votes {
+            *totals.entry(value.as_str()).or_insert(0) += self.validators[validator];
+        }
+        totals
+            .into_iter()
+            .find(|(_, stake)| self.exceeds_two_thirds(*stake))
+            .map(|(value, _)| value.to_string())
+    }
+
+    /// Returns the value locked by a prevote supermajority, if any.
+    pub fn is_locked(&self) -> Option<String> {
+        self.leading_value(&self.prevotes)
+    }
+
+    /// Returns the value finalized by a precommit supermajority matching the locked prevote
+    /// value, if any.
+    pub fn is_finalized(&self) -> Option<String> {
+        let locked = self.is_locked()?;
+        let committed = self.leading_value(&self.precommits)?;
+        (committed == locked).then_some(committed)
+    }
+
+    /// Convenience check: `true` if `value`'s current prevote stake alone already exceeds the
+    /// two-thirds threshold.
+    pub fn has_quorum(&self, value: &str) -> bool {
+        let stake: u64 = self
+            .prevotes
+            .iter()
+            .filter(|(_, v)| v.as_str() == value)
+            .map(|(validator, _)| self.validators[validator])
+            .sum();
+        self.exceeds_two_thirds(stake)
     }
 }
 
@@ -27,10 +123,86 @@ impl GovQuorum {
 mod tests {
     use super::*;
 
+    fn round() -> BftRound {
+        BftRound::new(HashMap::from([
+            ("v1".to_string(), 25),
+            ("v2".to_string(), 25),
+            ("v3".to_string(), 25),
+            ("v4".to_string(), 25),
+        ]))
+    }
+
+    #[test]
+    fn locks_once_prevotes_exceed_two_thirds_stake() {
+        let mut r = round();
+        r.record_prevote("v1", "block-a").unwrap();
+        r.record_prevote("v2", "block-a").unwrap();
+        assert_eq!(r.is_locked(), None);
+
+        r.record_prevote("v3", "block-a").unwrap();
+        assert_eq!(r.is_locked(), Some("block-a".to_string()));
+        assert!(r.has_quorum("block-a"));
+    }
+
+    #[test]
+    fn finalizes_once_precommits_match_the_locked_value() {
+        let mut r = round();
+        for v in ["v1", "v2", "v3"] {
+            r.record_prevote(v, "block-a").unwrap();
+        }
+        assert_eq!(r.is_finalized(), None);
+
+        for v in ["v1", "v2", "v3"] {
+            r.record_precommit(v, "block-a").unwrap();
+        }
+        assert_eq!(r.is_finalized(), Some("block-a".to_string()));
+    }
+
+    #[test]
+    fn finalization_requires_precommit_to_match_the_locked_value() {
+        let mut r = round();
+        for v in ["v1", "v2", "v3"] {
+            r.record_prevote(v, "block-a").unwrap();
+        }
+        for v in ["v1", "v2", "v3"] {
+            r.record_precommit(v, "block-b").unwrap();
+        }
+        assert_eq!(r.is_finalized(), None);
+    }
+
+    #[test]
+    fn double_vote_in_a_phase_is_rejected() {
+        let mut r = round();
+        r.record_prevote("v1", "block-a").unwrap();
+        assert_eq!(
+            r.record_prevote("v1", "block-b"),
+            Err(GovQuorumError::DoubleVote)
+        );
+        // Re-voting the same value is not an equivocation.
+        assert!(r.record_prevote("v1", "block-a").is_ok());
+    }
+
+    #[test]
+    fn unknown_validator_is_rejected() {
+        let mut r = round();
+        assert_eq!(
+            r.record_prevote("ghost", "block-a"),
+            Err(GovQuorumError::UnknownValidator)
+        );
+    }
+
     #[test]
-    fn quorum_check() {
-        let q = GovQuorum::new(0.6);
-        assert!(q.has_quorum(6, 10));
-        assert!(!q.has_quorum(5, 10));
+    fn exactly_two_thirds_stake_does_not_meet_the_strict_threshold() {
+        let mut r = BftRound::new(HashMap::from([
+            ("v1".to_string(), 1),
+            ("v2".to_string(), 1),
+            ("v3".to_string(), 1),
+        ]));
+        r.record_prevote("v1", "block-a").unwrap();
+        r.record_prevote("v2", "block-a").unwrap();
+        // 2 of 3 stake is exactly two-thirds, not strictly more.
+        assert_eq!(r.is_locked(), None);
+        r.record_prevote("v3", "block-a").unwrap();
+        assert_eq!(r.is_locked(), Some("block-a".to_string()));
     }
 }
@@ -1,6 +1,57 @@
 //! Simple health monitor tracking metrics.
 
 use std::collections::HashMap;
+use std::net::{SocketAddr, UdpSocket};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// Seconds between the NTP epoch (1900-01-01) and the Unix epoch (1970-01-01).
+const NTP_UNIX_EPOCH_DELTA: u64 = 2_208_988_800;
+const NTP_PACKET_SIZE: usize = 48;
+const QUERY_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Metric key under which [`HealthMonitor::check_time_offset`] stores its result.
+pub const CLOCK_OFFSET_METRIC: &str = "clock_offset_secs";
+
+fn system_time_to_ntp(t: SystemTime) -> f64 {
+    let dur = t.duration_since(UNIX_EPOCH).unwrap_or_default();
+    dur.as_secs_f64() + NTP_UNIX_EPOCH_DELTA as f64
+}
+
+fn read_ntp_timestamp(buf: &[u8]) -> f64 {
+    let secs = u32::from_be_bytes(buf[0..4].try_into().unwrap());
+    let frac = u32::from_be_bytes(buf[4..8].try_into().unwrap());
+    secs as f64 + frac as f64 / (u32::MAX as f64 + 1.0)
+}
+
+fn write_ntp_timestamp(buf: &mut [u8], value: f64) {
+    let secs = value.trunc() as u32;
+    let frac = (value.fract() * (u32::MAX as f64 + 1.0)) as u32;
+    buf[0..4].copy_from_slice(&secs.to_be_bytes());
+    buf[4..8].copy_from_slice(&frac.to_be_bytes());
+}
+
+/// Performs a single SNTP exchange with `server`, returning `(offset, round_trip_delay)`
+/// in seconds, or `None` if the request times out or the response is malformed.
+fn sntp_query(socket: &UdpSocket, server: SocketAddr) -> Option<(f64, f64)> {
+    let mut request = [0u8; NTP_PACKET_SIZE];
+    request[0] = 0x1B; // LI = 0, VN = 3, Mode = 3 (client)
+    let t0 = system_time_to_ntp(SystemTime::now());
+    write_ntp_timestamp(&mut request[40..48], t0);
+
+    socket.send_to(&request, server).ok()?;
+    let mut response = [0u8; NTP_PACKET_SIZE];
+    let (n, _) = socket.recv_from(&mut response).ok()?;
+    let t3 = system_time_to_ntp(SystemTime::now());
+    if n < NTP_PACKET_SIZE {
+        return None;
+    }
+
+    let t1 = read_ntp_timestamp(&response[32..40]); // server receive timestamp
+    let t2 = read_ntp_timestamp(&response[40..48]); // server transmit timestamp
+    let offset = ((t1 - t0) + (t2 - t3)) / 2.0;
+    let delay = (t3 - t0) - (t2 - t1);
+    Some((offset, delay))
+}
 
 /// Maintains a set of health metrics.
 pub struct HealthMonitor {
@@ -29,13 +80,45 @@ impl HealthMonitor {
     pub fn within(&self, metric: &str, min: f64, max: f64) -> bool {
         self.metrics
             .get(metric)
-            .map_or(false, |v| *v >= min && *v <= max)
+            .is_some_and(|v| *v >= min && *v <= max)
+    }
+
+    /// Queries `servers` in order via SNTP until one responds, storing the measured clock
+    /// offset (in seconds, positive meaning the local clock is behind) as the
+    /// [`CLOCK_OFFSET_METRIC`] metric. Returns `None` if every server times out.
+    pub fn check_time_offset(&mut self, servers: &[SocketAddr]) -> Option<f64> {
+        let socket = UdpSocket::bind("0.0.0.0:0").ok()?;
+        socket.set_read_timeout(Some(QUERY_TIMEOUT)).ok()?;
+        socket.set_write_timeout(Some(QUERY_TIMEOUT)).ok()?;
+
+        for &server in servers {
+            if let Some((offset, _delay)) = sntp_query(&socket, server) {
+                self.update(CLOCK_OFFSET_METRIC, offset);
+                return Some(offset);
+            }
+        }
+        None
+    }
+
+    /// Returns `true` if a clock offset has been measured and its magnitude is within
+    /// `max_offset_secs`. Returns `false` if no measurement is available (e.g. every
+    /// server timed out) or the offset exceeds the threshold.
+    pub fn is_clock_healthy(&self, max_offset_secs: f64) -> bool {
+        self.value(CLOCK_OFFSET_METRIC)
+            .is_some_and(|offset| offset.abs() <= max_offset_secs)
+    }
+}
+
+impl Default for HealthMonitor {
+    fn default() -> Self {
+        Self::new()
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use std::thread;
 
     #[test]
     fn monitor_metrics() {
@@ -45,4 +128,55 @@ mod tests {
         assert!(!m.within("cpu", 0.0, 0.4));
         assert_eq!(m.value("cpu"), Some(0.5));
     }
+
+    /// Spawns a loopback SNTP server that always reports itself exactly `skew` seconds
+    /// ahead of the caller, and returns its bound address.
+    fn spawn_fake_ntp_server(skew: f64) -> SocketAddr {
+        let server = UdpSocket::bind("127.0.0.1:0").expect("bind fake ntp server");
+        let addr = server.local_addr().unwrap();
+        thread::spawn(move || {
+            let mut buf = [0u8; NTP_PACKET_SIZE];
+            if let Ok((_, client)) = server.recv_from(&mut buf) {
+                let server_now = system_time_to_ntp(SystemTime::now()) + skew;
+                let mut response = [0u8; NTP_PACKET_SIZE];
+                write_ntp_timestamp(&mut response[32..40], server_now);
+                write_ntp_timestamp(&mut response[40..48], server_now);
+                let _ = server.send_to(&response, client);
+            }
+        });
+        addr
+    }
+
+    #[test]
+    fn check_time_offset_reports_server_skew() {
+        let server = spawn_fake_ntp_server(5.0);
+        let mut monitor = HealthMonitor::new();
+        let offset = monitor
+            .check_time_offset(&[server])
+            .expect("server should respond");
+        assert!((offset - 5.0).abs() < 0.5, "offset was {offset}");
+        assert_eq!(monitor.value(CLOCK_OFFSET_METRIC), Some(offset));
+    }
+
+    #[test]
+    fn is_clock_healthy_respects_threshold() {
+        let server = spawn_fake_ntp_server(10.0);
+        let mut monitor = HealthMonitor::new();
+        monitor.check_time_offset(&[server]).unwrap();
+        assert!(monitor.is_clock_healthy(20.0));
+        assert!(!monitor.is_clock_healthy(1.0));
+    }
+
+    #[test]
+    fn is_clock_healthy_false_without_measurement() {
+        let monitor = HealthMonitor::new();
+        assert!(!monitor.is_clock_healthy(1.0));
+    }
+
+    #[test]
+    fn check_time_offset_none_when_all_servers_time_out() {
+        let mut monitor = HealthMonitor::new();
+        let unreachable: SocketAddr = "127.0.0.1:1".parse().unwrap();
+        assert_eq!(monitor.check_time_offset(&[unreachable]), None);
+    }
 }
@@ -0,0 +1,138 @@
+//! Minimal pure-Rust Keccak-256.
+//!
+//! This is the original Keccak padding (domain byte `0x01`) used throughout the Ethereum
+//! stack, not NIST's SHA3-256 (`0x06`). Shared by any contract module that needs a
+//! keccak-compatible digest for a Merkle trie or proof (state snapshots, cross-chain proofs).
+
+const RC: [u64; 24] = [
+    0x0000000000000001,
+    0x0000000000008082,
+    0x800000000000808a,
+    0x8000000080008000,
+    0x000000000000808b,
+    0x0000000080000001,
+    0x8000000080008081,
+    0x8000000000008009,
+    0x000000000000008a,
+    0x0000000000000088,
+    0x0000000080008009,
+    0x000000008000000a,
+    0x000000008000808b,
+    0x800000000000008b,
+    0x8000000000008089,
+    0x8000000000008003,
+    0x8000000000008002,
+    0x8000000000000080,
+    0x000000000000800a,
+    0x800000008000000a,
+    0x8000000080008081,
+    0x8000000000008080,
+    0x0000000080000001,
+    0x8000000080008008,
+];
+
+const RHO: [u32; 24] = [
+    1, 3, 6, 10, 15, 21, 28, 36, 45, 55, 2, 14, 27, 41, 56, 8, 25, 43, 62, 18, 39, 61, 20, 44,
+];
+
+const PI: [usize; 24] = [
+    10, 7, 11, 17, 18, 3, 5, 16, 8, 21, 24, 4, 15, 23, 19, 13, 12, 2, 20, 14, 22, 9, 6, 1,
+];
+
+const RATE_BYTES: usize = 136; // 1088-bit rate for a 256-bit capacity/output
+
+fn keccak_f(a: &mut [u64; 25]) {
+    for rc in RC {
+        // Theta
+        let mut c = [0u64; 5];
+        for (x, slot) in c.iter_mut().enumerate() {
+            *slot = a[x] ^ a[x + 5] ^ a[x + 10] ^ a[x + 15] ^ a[x + 20];
+        }
+        let mut d = [0u64; 5];
+        for x in 0..5 {
+            d[x] = c[(x + 4) % 5] ^ c[(x + 1) % 5].rotate_left(1);
+        }
+        for x in 0..5 {
+            for y in 0..5 {
+                a[x + y * 5] ^= d[x];
+            }
+        }
+
+        // Rho and Pi
+        let mut last = a[1];
+        for x in 0..24 {
+            let tmp = a[PI[x]];
+            a[PI[x]] = last.rotate_left(RHO[x]);
+            last = tmp;
+        }
+
+        // Chi
+        for y in 0..5 {
+            let base = y * 5;
+            let row = [
+                a[base],
+                a[base + 1],
+                a[base + 2],
+                a[base + 3],
+                a[base + 4],
+            ];
+            for x in 0..5 {
+                a[base + x] = row[x] ^ (!row[(x + 1) % 5] & row[(x + 2) % 5]);
+            }
+        }
+
+        // Iota
+        a[0] ^= rc;
+    }
+}
+
+/// Hashes `data` with Keccak-256, returning the 32-byte digest.
+pub fn keccak256(data: &[u8]) -> [u8; 32] {
+    let mut state = [0u64; 25];
+
+    let mut padded = data.to_vec();
+    padded.push(0x01);
+    while !padded.len().is_multiple_of(RATE_BYTES) {
+        padded.push(0x00);
+    }
+    let last = padded.len() - 1;
+    padded[last] |= 0x80;
+
+    for block in padded.chunks(RATE_BYTES) {
+        for (i, word) in block.chunks(8).enumerate() {
+            let mut lane = [0u8; 8];
+            lane[..word.len()].copy_from_slice(word);
+            state[i] ^= u64::from_le_bytes(lane);
+        }
+        keccak_f(&mut state);
+    }
+
+    let mut out = [0u8; 32];
+    for (i, lane) in state[..4].iter().enumerate() {
+        out[i * 8..i * 8 + 8].copy_from_slice(&lane.to_le_bytes());
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hex(bytes: &[u8; 32]) -> String {
+        bytes.iter().map(|b| format!("{b:02x}")).collect()
+    }
+
+    #[test]
+    fn empty_input_matches_known_vector() {
+        // The well-known Keccak-256 digest of the empty byte string.
+        assert_eq!(
+            hex(&keccak256(&[])),
+            "c5d2460186f7233c927e7db2dcc703c0e500b653ca82273b7bfad8045d85a470"
+        );
+    }
+
+    #[test]
+    fn different_inputs_hash_differently() {
+        assert_ne!(keccak256(b"a"), keccak256(b"b"));
+    }
+}
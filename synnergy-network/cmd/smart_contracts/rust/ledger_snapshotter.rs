@@ -1,8 +1,119 @@
-//! Maintains ledger snapshots.
+//! Maintains ledger snapshots as verifiable Merkle state roots.
+//!
+//! Each snapshot commits to its `(key, value)` state with a binary Merkle tree: leaves are
+//! `keccak256(key || value)` sorted by key, and each level hashes `keccak256(left || right)`,
+//! duplicating the last node when a level is odd. This lets a light client verify a single
+//! key/value against the snapshot's 32-byte root without holding the whole state.
 
-/// Stores arbitrary textual representations of ledger state.
+mod keccak;
+
+use keccak::keccak256;
+use std::collections::BTreeMap;
+
+fn leaf_hash(key: &[u8], value: &[u8]) -> [u8; 32] {
+    let mut buf = Vec::with_capacity(key.len() + value.len());
+    buf.extend_from_slice(key);
+    buf.extend_from_slice(value);
+    keccak256(&buf)
+}
+
+fn node_hash(left: &[u8; 32], right: &[u8; 32]) -> [u8; 32] {
+    let mut buf = [0u8; 64];
+    buf[..32].copy_from_slice(left);
+    buf[32..].copy_from_slice(right);
+    keccak256(&buf)
+}
+
+/// A single point-in-time commitment to ledger state.
+pub struct Snapshot {
+    entries: BTreeMap<Vec<u8>, Vec<u8>>,
+    /// `levels[0]` holds the sorted leaf hashes, `levels.last()` holds the single root.
+    levels: Vec<Vec<[u8; 32]>>,
+}
+
+impl Snapshot {
+    fn build(entries: BTreeMap<Vec<u8>, Vec<u8>>) -> Self {
+        let leaves: Vec<[u8; 32]> = entries.iter().map(|(k, v)| leaf_hash(k, v)).collect();
+        let mut levels = vec![if leaves.is_empty() {
+            vec![[0u8; 32]]
+        } else {
+            leaves
+        }];
+        while levels.last().unwrap().len() > 1 {
+            let prev = levels.last().unwrap();
+            let mut next = Vec::with_capacity(prev.len().div_ceil(2));
+            for pair in prev.chunks(2) {
+                let left = pair[0];
+                let right = pair.get(1).copied().unwrap_or(left);
+                next.push(node_hash(&left, &right));
+            }
+            levels.push(next);
+        }
+        Self { entries, levels }
+    }
+
+    /// Returns the 32-byte Merkle root of this snapshot's state.
+    pub fn root(&self) -> [u8; 32] {
+        self.levels.last().unwrap()[0]
+    }
+
+    /// Returns the stored value for `key`, if present in this snapshot.
+    pub fn get(&self, key: &[u8]) -> Option<&[u8]> {
+        self.entries.get(key).map(|v| v.as_slice())
+    }
+
+    /// Builds an inclusion proof for `key`: a sibling hash plus a left/right flag (`true`
+    /// means the proven node is the left child) for each level from leaf to root. Returns
+    /// `None` if `key` is not part of this snapshot.
+    pub fn prove(&self, key: &[u8]) -> Option<Vec<([u8; 32], bool)>> {
+        let mut index = self.entries.keys().position(|k| k.as_slice() == key)?;
+        let mut proof = Vec::with_capacity(self.levels.len() - 1);
+        for level in &self.levels[..self.levels.len() - 1] {
+            let is_left = index % 2 == 0;
+            let sibling_idx = if is_left { index + 1 } else { index - 1 };
+            let sibling = level.get(sibling_idx).copied().unwrap_or(level[index]);
+            proof.push((sibling, is_left));
+            index /= 2;
+        }
+        Some(proof)
+    }
+
+    /// Returns the keys whose values differ (added, removed, or changed) between `self` and
+    /// `other`, so a light client can sync incrementally instead of re-fetching full state.
+    pub fn diff(&self, other: &Self) -> Vec<Vec<u8>> {
+        let mut changed: Vec<Vec<u8>> = self
+            .entries
+            .iter()
+            .filter(|(k, v)| other.entries.get(*k) != Some(*v))
+            .map(|(k, _)| k.clone())
+            .collect();
+        changed.extend(
+            other
+                .entries
+                .keys()
+                .filter(|k| !self.entries.contains_key(*k))
+                .cloned(),
+        );
+        changed
+    }
+}
+
+/// Recomputes the Merkle root for `key`/`value` against `proof` and compares it to `root`.
+pub fn verify(root: [u8; 32], key: &[u8], value: &[u8], proof: &[([u8; 32], bool)]) -> bool {
+    let mut hash = leaf_hash(key, value);
+    for (sibling, is_left) in proof {
+        hash = if *is_left {
+            node_hash(&hash, sibling)
+        } else {
+            node_hash(sibling, &hash)
+        };
+    }
+    hash == root
+}
+
+/// Maintains a history of ledger [`Snapshot`]s.
 pub struct LedgerSnapshotter {
-    snapshots: Vec<String>,
+    snapshots: Vec<Snapshot>,
 }
 
 impl LedgerSnapshotter {
@@ -13,14 +124,26 @@ impl LedgerSnapshotter {
         }
     }
 
-    /// Stores a new snapshot.
-    pub fn take_snapshot<S: Into<String>>(&mut self, data: S) {
-        self.snapshots.push(data.into());
+    /// Commits `state` as a new snapshot and returns its Merkle root.
+    pub fn take_snapshot<I, K, V>(&mut self, state: I) -> [u8; 32]
+    where
+        I: IntoIterator<Item = (K, V)>,
+        K: Into<Vec<u8>>,
+        V: Into<Vec<u8>>,
+    {
+        let entries = state
+            .into_iter()
+            .map(|(k, v)| (k.into(), v.into()))
+            .collect();
+        let snapshot = Snapshot::build(entries);
+        let root = snapshot.root();
+        self.snapshots.push(snapshot);
+        root
     }
 
     /// Returns the latest snapshot if available.
-    pub fn latest(&self) -> Option<&str> {
-        self.snapshots.last().map(|s| s.as_str())
+    pub fn latest(&self) -> Option<&Snapshot> {
+        self.snapshots.last()
     }
 
     /// Returns the number of snapshots held.
@@ -29,6 +152,12 @@ impl LedgerSnapshotter {
     }
 }
 
+impl Default for LedgerSnapshotter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -36,9 +165,52 @@ mod tests {
     #[test]
     fn snapshots_work() {
         let mut s = LedgerSnapshotter::new();
-        s.take_snapshot("state1");
-        s.take_snapshot("state2");
-        assert_eq!(s.latest(), Some("state2"));
+        s.take_snapshot([("a", "1")]);
+        s.take_snapshot([("a", "2"), ("b", "3")]);
+        assert_eq!(s.latest().unwrap().get(b"a"), Some(b"2".as_slice()));
         assert_eq!(s.count(), 2);
     }
+
+    #[test]
+    fn root_changes_with_state() {
+        let mut s = LedgerSnapshotter::new();
+        let root1 = s.take_snapshot([("a", "1")]);
+        let root2 = s.take_snapshot([("a", "2")]);
+        assert_ne!(root1, root2);
+    }
+
+    #[test]
+    fn proof_verifies_against_root() {
+        let mut s = LedgerSnapshotter::new();
+        s.take_snapshot([("alice", "100"), ("bob", "200"), ("carol", "300")]);
+        let snapshot = s.latest().unwrap();
+        let root = snapshot.root();
+        let proof = snapshot.prove(b"bob").expect("bob is in the snapshot");
+        assert!(verify(root, b"bob", b"200", &proof));
+        assert!(!verify(root, b"bob", b"999", &proof));
+    }
+
+    #[test]
+    fn prove_returns_none_for_missing_key() {
+        let mut s = LedgerSnapshotter::new();
+        s.take_snapshot([("alice", "100")]);
+        assert!(s.latest().unwrap().prove(b"dave").is_none());
+    }
+
+    #[test]
+    fn single_leaf_root_is_its_own_leaf_hash() {
+        let mut s = LedgerSnapshotter::new();
+        let root = s.take_snapshot([("only", "value")]);
+        assert_eq!(root, leaf_hash(b"only", b"value"));
+    }
+
+    #[test]
+    fn diff_reports_added_removed_and_changed_keys() {
+        let mut s = LedgerSnapshotter::new();
+        s.take_snapshot([("a", "1"), ("b", "2"), ("d", "4")]);
+        s.take_snapshot([("a", "1"), ("b", "9"), ("c", "3")]);
+        let mut changed = s.latest().unwrap().diff(&s.snapshots[0]);
+        changed.sort();
+        assert_eq!(changed, vec![b"b".to_vec(), b"c".to_vec(), b"d".to_vec()]);
+    }
 }
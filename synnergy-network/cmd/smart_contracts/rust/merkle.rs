@@ -0,0 +1,135 @@
+//! A general-purpose binary Merkle tree over pre-hashed leaves.
+//!
+//! Unlike `ledger_snapshotter`'s key/value tree (keccak-hashed, sorted by key), this tree
+//! commits to an ordered `Vec<[u8; 32]>` of leaves the caller has already hashed, and uses
+//! SHA-256 to match the NIST-standard digests external verifiers expect for fraud proofs.
+//! Parents are `sha256(left || right)`, duplicating the last node when a level is odd.
+
+#[path = "sha256.rs"]
+mod sha256;
+
+use sha256::sha256;
+
+fn node_hash(left: &[u8; 32], right: &[u8; 32]) -> [u8; 32] {
+    let mut buf = [0u8; 64];
+    buf[..32].copy_from_slice(left);
+    buf[32..].copy_from_slice(right);
+    sha256(&buf)
+}
+
+/// A Merkle tree committed to an ordered list of leaf hashes.
+pub struct MerkleTree {
+    /// `levels[0]` holds the leaves, `levels.last()` holds the single root.
+    levels: Vec<Vec<[u8; 32]>>,
+    leaf_count: usize,
+}
+
+impl MerkleTree {
+    /// Builds a tree over `leaves`. An empty input yields a zero root.
+    pub fn new(leaves: Vec<[u8; 32]>) -> Self {
+        let leaf_count = leaves.len();
+        let mut levels = vec![if leaves.is_empty() {
+            vec![[0u8; 32]]
+        } else {
+            leaves
+        }];
+        while levels.last().unwrap().len() > 1 {
+            let prev = levels.last().unwrap();
+            let mut next = Vec::with_capacity(prev.len().div_ceil(2));
+            for pair in prev.chunks(2) {
+                let left = pair[0];
+                let right = pair.get(1).copied().unwrap_or(left);
+                next.push(node_hash(&left, &right));
+            }
+            levels.push(next);
+        }
+        Self { levels, leaf_count }
+    }
+
+    /// Returns the 32-byte root, or the zero hash for an empty tree.
+    pub fn root(&self) -> [u8; 32] {
+        self.levels.last().unwrap()[0]
+    }
+
+    /// Builds an inclusion proof for the leaf at `index`: a sibling hash plus a left/right
+    /// flag (`true` means the proven node is the left child) for each level from leaf to
+    /// root. Returns `None` if `index` is out of bounds.
+    pub fn proof(&self, index: usize) -> Option<Vec<([u8; 32], bool)>> {
+        if index >= self.leaf_count {
+            return None;
+        }
+        let mut index = index;
+        let mut proof = Vec::with_capacity(self.levels.len() - 1);
+        for level in &self.levels[..self.levels.len() - 1] {
+            let is_left = index.is_multiple_of(2);
+            let sibling_idx = if is_left { index + 1 } else { index - 1 };
+            let sibling = level.get(sibling_idx).copied().unwrap_or(level[index]);
+            proof.push((sibling, is_left));
+            index /= 2;
+        }
+        Some(proof)
+    }
+}
+
+/// Hashes raw data into a leaf suitable for [`MerkleTree::new`].
+pub fn hash_leaf(data: &[u8]) -> [u8; 32] {
+    sha256(data)
+}
+
+/// Recomputes the Merkle root for `leaf` against `proof` and compares it to `root`.
+pub fn verify(root: [u8; 32], leaf: [u8; 32], proof: &[([u8; 32], bool)]) -> bool {
+    let mut hash = leaf;
+    for (sibling, is_left) in proof {
+        hash = if *is_left {
+            node_hash(&hash, sibling)
+        } else {
+            node_hash(sibling, &hash)
+        };
+    }
+    hash == root
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn empty_tree_has_a_zero_root() {
+        let tree = MerkleTree::new(vec![]);
+        assert_eq!(tree.root(), [0u8; 32]);
+    }
+
+    #[test]
+    fn single_leaf_root_is_the_leaf_itself() {
+        let leaf = sha256(b"only");
+        let tree = MerkleTree::new(vec![leaf]);
+        assert_eq!(tree.root(), leaf);
+    }
+
+    #[test]
+    fn proof_verifies_against_root_for_every_leaf() {
+        let leaves: Vec<[u8; 32]> = (0..5).map(|i| sha256(format!("leaf-{i}").as_bytes())).collect();
+        let tree = MerkleTree::new(leaves.clone());
+        let root = tree.root();
+        for (i, leaf) in leaves.iter().enumerate() {
+            let proof = tree.proof(i).expect("index in bounds");
+            assert!(verify(root, *leaf, &proof));
+        }
+    }
+
+    #[test]
+    fn proof_rejects_a_tampered_leaf() {
+        let leaves: Vec<[u8; 32]> = (0..4).map(|i| sha256(format!("leaf-{i}").as_bytes())).collect();
+        let tree = MerkleTree::new(leaves.clone());
+        let root = tree.root();
+        let proof = tree.proof(0).unwrap();
+        assert!(!verify(root, leaves[1], &proof));
+    }
+
+    #[test]
+    fn proof_returns_none_for_out_of_bounds_index() {
+        let leaves: Vec<[u8; 32]> = (0..3).map(|i| sha256(format!("leaf-{i}").as_bytes())).collect();
+        let tree = MerkleTree::new(leaves);
+        assert!(tree.proof(3).is_none());
+    }
+}
@@ -1,6 +1,6 @@
-//! Lightweight message queue.
+//! Lightweight message queues.
 
-use std::collections::VecDeque;
+use std::collections::{BinaryHeap, VecDeque};
 
 /// A simple FIFO message queue.
 pub struct MessageQueue<T> {
@@ -29,6 +29,138 @@ impl<T> MessageQueue<T> {
     pub fn len(&self) -> usize {
         self.queue.len()
     }
+
+    /// Returns `true` if the queue holds no messages.
+    pub fn is_empty(&self) -> bool {
+        self.queue.is_empty()
+    }
+}
+
+impl<T> Default for MessageQueue<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+struct Entry<T> {
+    priority: u8,
+    seq: u64,
+    msg: T,
+}
+
+impl<T> PartialEq for Entry<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.priority == other.priority && self.seq == other.seq
+    }
+}
+
+impl<T> Eq for Entry<T> {}
+
+impl<T> PartialOrd for Entry<T> {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<T> Ord for Entry<T> {
+    /// Higher priority first; among equal priorities, lower (earlier) `seq` first.
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        self.priority
+            .cmp(&other.priority)
+            .then_with(|| other.seq.cmp(&self.seq))
+    }
+}
+
+/// Policy applied by [`PriorityMessageQueue::try_enqueue`] when the queue is at capacity.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OverflowPolicy {
+    /// Reject the incoming message, leaving the queue unchanged.
+    RejectIncoming,
+    /// Evict the current lowest-priority message to make room for the incoming one.
+    EvictLowestPriority,
+}
+
+/// A bounded message queue that dequeues higher-priority messages first, breaking ties by
+/// insertion order (FIFO within a priority level).
+pub struct PriorityMessageQueue<T> {
+    heap: BinaryHeap<Entry<T>>,
+    capacity: usize,
+    policy: OverflowPolicy,
+    next_seq: u64,
+}
+
+impl<T> PriorityMessageQueue<T> {
+    /// Creates an empty queue bounded to `capacity` messages, applying `policy` on overflow.
+    pub fn new(capacity: usize, policy: OverflowPolicy) -> Self {
+        Self {
+            heap: BinaryHeap::new(),
+            capacity,
+            policy,
+            next_seq: 0,
+        }
+    }
+
+    /// Returns the number of messages currently queued.
+    pub fn len(&self) -> usize {
+        self.heap.len()
+    }
+
+    /// Returns `true` if the queue holds no messages.
+    pub fn is_empty(&self) -> bool {
+        self.heap.is_empty()
+    }
+
+    /// Returns `true` if the queue is at capacity.
+    pub fn is_full(&self) -> bool {
+        self.heap.len() >= self.capacity
+    }
+
+    /// Enqueues `msg` at `priority`. If the queue is full, applies the configured overflow
+    /// policy: [`OverflowPolicy::RejectIncoming`] returns `msg` back to the caller unqueued;
+    /// [`OverflowPolicy::EvictLowestPriority`] drops the current lowest-priority message to
+    /// make room, unless `msg` itself would be the new lowest-priority entry, in which case
+    /// `msg` is rejected instead.
+    pub fn try_enqueue(&mut self, priority: u8, msg: T) -> Result<(), T> {
+        if self.capacity == 0 {
+            return Err(msg);
+        }
+        if self.is_full() {
+            match self.policy {
+                OverflowPolicy::RejectIncoming => return Err(msg),
+                OverflowPolicy::EvictLowestPriority => {
+                    let lowest = self.heap.iter().min().expect("queue is full, not empty");
+                    if priority <= lowest.priority {
+                        return Err(msg);
+                    }
+                    pop_min(&mut self.heap);
+                }
+            }
+        }
+        let seq = self.next_seq;
+        self.next_seq += 1;
+        self.heap.push(Entry { priority, seq, msg });
+        Ok(())
+    }
+
+    /// Removes and returns the highest-priority message, breaking ties in FIFO order.
+    pub fn dequeue(&mut self) -> Option<T> {
+        self.heap.pop().map(|entry| entry.msg)
+    }
+}
+
+/// Removes and returns the lowest-priority entry from `heap` by rebuilding it without the
+/// minimum element (`BinaryHeap` only exposes efficient access to the maximum).
+fn pop_min<T>(heap: &mut BinaryHeap<Entry<T>>) -> Entry<T> {
+    let mut items: Vec<Entry<T>> = std::mem::take(heap).into_vec();
+    let min_index = items
+        .iter()
+        .enumerate()
+        .min_by(|(_, a), (_, b)| a.cmp(b))
+        .map(|(i, _)| i)
+        .expect("heap is non-empty");
+    let min = items.remove(min_index);
+    *heap = BinaryHeap::from(items);
+    min
 }
 
 #[cfg(test)]
@@ -36,7 +168,7 @@ mod tests {
     use super::*;
 
     #[test]
-    fn queue_works() {
+    fn fifo_queue_works() {
         let mut q = MessageQueue::new();
         q.enqueue(1);
         q.enqueue(2);
@@ -45,4 +177,61 @@ mod tests {
         assert_eq!(q.dequeue(), Some(2));
         assert_eq!(q.dequeue(), None);
     }
+
+    #[test]
+    fn higher_priority_dequeues_first() {
+        let mut q = PriorityMessageQueue::new(10, OverflowPolicy::RejectIncoming);
+        q.try_enqueue(1, "bulk").unwrap();
+        q.try_enqueue(9, "consensus").unwrap();
+        q.try_enqueue(5, "control").unwrap();
+        assert_eq!(q.dequeue(), Some("consensus"));
+        assert_eq!(q.dequeue(), Some("control"));
+        assert_eq!(q.dequeue(), Some("bulk"));
+    }
+
+    #[test]
+    fn equal_priority_breaks_ties_fifo() {
+        let mut q = PriorityMessageQueue::new(10, OverflowPolicy::RejectIncoming);
+        q.try_enqueue(5, "first").unwrap();
+        q.try_enqueue(5, "second").unwrap();
+        assert_eq!(q.dequeue(), Some("first"));
+        assert_eq!(q.dequeue(), Some("second"));
+    }
+
+    #[test]
+    fn reject_policy_drops_incoming_message_when_full() {
+        let mut q = PriorityMessageQueue::new(2, OverflowPolicy::RejectIncoming);
+        q.try_enqueue(1, "a").unwrap();
+        q.try_enqueue(1, "b").unwrap();
+        assert!(q.is_full());
+        assert_eq!(q.try_enqueue(9, "c"), Err("c"));
+        assert_eq!(q.len(), 2);
+    }
+
+    #[test]
+    fn evict_policy_drops_the_lowest_priority_message_when_full() {
+        let mut q = PriorityMessageQueue::new(2, OverflowPolicy::EvictLowestPriority);
+        q.try_enqueue(1, "low").unwrap();
+        q.try_enqueue(5, "mid").unwrap();
+        assert!(q.try_enqueue(9, "high").is_ok());
+        assert_eq!(q.len(), 2);
+        assert_eq!(q.dequeue(), Some("high"));
+        assert_eq!(q.dequeue(), Some("mid"));
+    }
+
+    #[test]
+    fn evict_policy_rejects_an_incoming_message_that_would_itself_be_lowest() {
+        let mut q = PriorityMessageQueue::new(2, OverflowPolicy::EvictLowestPriority);
+        q.try_enqueue(5, "mid").unwrap();
+        q.try_enqueue(9, "high").unwrap();
+        assert_eq!(q.try_enqueue(1, "low"), Err("low"));
+        assert_eq!(q.len(), 2);
+    }
+
+    #[test]
+    fn zero_capacity_rejects_everything() {
+        let mut q: PriorityMessageQueue<&str> = PriorityMessageQueue::new(0, OverflowPolicy::EvictLowestPriority);
+        assert_eq!(q.try_enqueue(1, "a"), Err("a"));
+        assert!(q.is_full());
+    }
 }
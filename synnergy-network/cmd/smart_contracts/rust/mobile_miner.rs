@@ -1,40 +1,61 @@
 //! Mobile miner stub.
+//!
+//! The mined-block counter is read and written through a caller-supplied [`Storage`] handle
+//! instead of an owned field, so the same miner can be backed by an in-memory store in tests
+//! and a persistent one in production without changing callers.
+
+#[path = "storage.rs"]
+mod storage;
+
+use storage::Storage;
+
+const MINED_BLOCKS_KEY: &[u8] = b"mined_blocks";
 
 /// Represents a miner operating from a mobile device.
 pub struct MobileMiner {
     pub hash_rate: u64,
-    mined_blocks: u64,
 }
 
 impl MobileMiner {
-    /// Creates a new miner with the specified hash rate.
+    /// Creates a new miner with the specified hash rate. State lives in whatever [`Storage`]
+    /// is passed to its methods.
     pub fn new(hash_rate: u64) -> Self {
-        Self {
-            hash_rate,
-            mined_blocks: 0,
-        }
+        Self { hash_rate }
     }
 
     /// Records the mining of a block.
-    pub fn mine_block(&mut self) {
-        self.mined_blocks += 1;
+    pub fn mine_block(&self, storage: &mut dyn Storage) {
+        let count = self.mined_blocks(storage) + 1;
+        storage.insert(MINED_BLOCKS_KEY.to_vec(), count.to_be_bytes().to_vec());
     }
 
     /// Returns the total number of mined blocks.
-    pub fn mined_blocks(&self) -> u64 {
-        self.mined_blocks
+    pub fn mined_blocks(&self, storage: &dyn Storage) -> u64 {
+        storage
+            .get(MINED_BLOCKS_KEY)
+            .map(|bytes| u64::from_be_bytes(bytes.try_into().unwrap_or([0; 8])))
+            .unwrap_or(0)
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use storage::InMemoryStorage;
 
     #[test]
     fn mining_increments() {
-        let mut miner = MobileMiner::new(10);
-        miner.mine_block();
-        miner.mine_block();
-        assert_eq!(miner.mined_blocks(), 2);
+        let miner = MobileMiner::new(10);
+        let mut storage = InMemoryStorage::new();
+        miner.mine_block(&mut storage);
+        miner.mine_block(&mut storage);
+        assert_eq!(miner.mined_blocks(&storage), 2);
+    }
+
+    #[test]
+    fn mined_blocks_persists_across_separate_miner_handles() {
+        let mut storage = InMemoryStorage::new();
+        MobileMiner::new(10).mine_block(&mut storage);
+        assert_eq!(MobileMiner::new(10).mined_blocks(&storage), 1);
     }
 }
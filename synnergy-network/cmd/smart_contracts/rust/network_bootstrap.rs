@@ -1,46 +1,86 @@
 //! Tracks nodes participating in bootstrap phase.
+//!
+//! State is read and written through a caller-supplied [`Storage`] handle instead of an
+//! owned `HashSet`, so the registry can be backed by an in-memory store in tests and a
+//! persistent one in production without changing callers.
 
-use std::collections::HashSet;
+#[path = "storage.rs"]
+mod storage;
 
-/// Registry for nodes joining the network during bootstrap.
-pub struct NetworkBootstrap {
-    nodes: HashSet<String>,
+use storage::Storage;
+
+const NODE_COUNT_KEY: &[u8] = b"node_count";
+
+fn node_key(id: &str) -> Vec<u8> {
+    let mut key = b"node:".to_vec();
+    key.extend_from_slice(id.as_bytes());
+    key
+}
+
+fn read_u64(storage: &dyn Storage, key: &[u8]) -> u64 {
+    storage
+        .get(key)
+        .map(|bytes| u64::from_be_bytes(bytes.try_into().unwrap_or([0; 8])))
+        .unwrap_or(0)
 }
 
+/// Registry for nodes joining the network during bootstrap.
+pub struct NetworkBootstrap;
+
 impl NetworkBootstrap {
-    /// Creates an empty registry.
+    /// Creates a registry. State lives in whatever [`Storage`] is passed to its methods.
     pub fn new() -> Self {
-        Self {
-            nodes: HashSet::new(),
-        }
+        Self
     }
 
     /// Adds a node identifier. Returns `true` if the node was newly inserted.
-    pub fn add_node<S: Into<String>>(&mut self, id: S) -> bool {
-        self.nodes.insert(id.into())
+    pub fn add_node(&self, storage: &mut dyn Storage, id: &str) -> bool {
+        let key = node_key(id);
+        if storage.get(&key).is_some() {
+            return false;
+        }
+        storage.insert(key, vec![1]);
+        let count = read_u64(storage, NODE_COUNT_KEY) + 1;
+        storage.insert(NODE_COUNT_KEY.to_vec(), count.to_be_bytes().to_vec());
+        true
     }
 
     /// Returns `true` if the node identifier exists in the registry.
-    pub fn has_node(&self, id: &str) -> bool {
-        self.nodes.contains(id)
+    pub fn has_node(&self, storage: &dyn Storage, id: &str) -> bool {
+        storage.get(&node_key(id)).is_some()
     }
 
     /// Returns the number of registered nodes.
-    pub fn node_count(&self) -> usize {
-        self.nodes.len()
+    pub fn node_count(&self, storage: &dyn Storage) -> u64 {
+        read_u64(storage, NODE_COUNT_KEY)
+    }
+}
+
+impl Default for NetworkBootstrap {
+    fn default() -> Self {
+        Self::new()
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use storage::InMemoryStorage;
 
     #[test]
     fn add_and_check_nodes() {
-        let mut nb = NetworkBootstrap::new();
-        assert!(nb.add_node("node1"));
-        assert!(!nb.add_node("node1"));
-        assert!(nb.has_node("node1"));
-        assert_eq!(nb.node_count(), 1);
+        let nb = NetworkBootstrap::new();
+        let mut storage = InMemoryStorage::new();
+        assert!(nb.add_node(&mut storage, "node1"));
+        assert!(!nb.add_node(&mut storage, "node1"));
+        assert!(nb.has_node(&storage, "node1"));
+        assert_eq!(nb.node_count(&storage), 1);
+    }
+
+    #[test]
+    fn state_persists_across_separate_registry_handles() {
+        let mut storage = InMemoryStorage::new();
+        NetworkBootstrap::new().add_node(&mut storage, "node1");
+        assert!(NetworkBootstrap::new().has_node(&storage, "node1"));
     }
 }
@@ -1,32 +1,127 @@
 use std::collections::HashMap;
 
-/// Simple in-memory oracle used for tests and demonstrations.
+/// A single price submission from one named source at a point in time.
+#[derive(Clone, Debug)]
+struct Sample {
+    source: String,
+    value: f64,
+    timestamp: u64,
+}
+
+/// Multi-source price oracle with staleness filtering and manipulation-resistant
+/// aggregation.
+///
+/// A single last-write-wins value is unsafe for downstream AMM/loan contracts, since one
+/// compromised or stale source can move the price. `update` records every submission keyed
+/// by `(key, source)`; [`OracleUpdater::median`] aggregates the freshest value per source
+/// into a quorum-gated median, and [`OracleUpdater::twap`] computes a time-weighted average
+/// across a window of history, making a single bad print far harder to exploit.
 ///
 /// The real implementation would interface with the Synnergy VM via
 /// custom opcodes defined in `opcode_dispatcher.go`.  For the purposes of
 /// this repository we provide a lightweight, fully synchronous Rust
 /// implementation that allows unit tests to exercise basic behaviour.
-#[derive(Default)]
 pub struct OracleUpdater {
-    values: HashMap<String, i64>,
+    samples: HashMap<String, Vec<Sample>>,
+    /// Minimum number of distinct fresh sources required before [`median`] returns a value.
+    quorum: usize,
 }
 
 impl OracleUpdater {
-    /// Create a new [`OracleUpdater`].
-    pub fn new() -> Self {
+    /// Creates a new [`OracleUpdater`] requiring at least `quorum` distinct fresh sources
+    /// before `median` returns a value.
+    pub fn new(quorum: usize) -> Self {
         Self {
-            values: HashMap::new(),
+            samples: HashMap::new(),
+            quorum,
         }
     }
 
-    /// Store a new oracle value for the given `key`.
-    pub fn update(&mut self, key: impl Into<String>, value: i64) {
-        self.values.insert(key.into(), value);
+    /// Records a new submission for `key` from `source`, observed at `timestamp`.
+    pub fn update(
+        &mut self,
+        key: impl Into<String>,
+        source: impl Into<String>,
+        value: f64,
+        timestamp: u64,
+    ) {
+        self.samples.entry(key.into()).or_default().push(Sample {
+            source: source.into(),
+            value,
+            timestamp,
+        });
+    }
+
+    /// Returns the most recently submitted value for `key`, regardless of source or
+    /// staleness.
+    pub fn get(&self, key: &str) -> Option<f64> {
+        self.samples
+            .get(key)?
+            .iter()
+            .max_by_key(|s| s.timestamp)
+            .map(|s| s.value)
+    }
+
+    /// Returns the median of the latest fresh submission from each source, discarding any
+    /// submission older than `ttl` relative to `now`. Returns `None` if fewer than `quorum`
+    /// distinct sources have a fresh submission.
+    pub fn median(&self, key: &str, now: u64, ttl: u64) -> Option<f64> {
+        let samples = self.samples.get(key)?;
+        let mut latest_per_source: HashMap<&str, (u64, f64)> = HashMap::new();
+        for sample in samples {
+            if now.saturating_sub(sample.timestamp) > ttl {
+                continue;
+            }
+            latest_per_source
+                .entry(sample.source.as_str())
+                .and_modify(|(ts, value)| {
+                    if sample.timestamp >= *ts {
+                        *ts = sample.timestamp;
+                        *value = sample.value;
+                    }
+                })
+                .or_insert((sample.timestamp, sample.value));
+        }
+
+        if latest_per_source.len() < self.quorum {
+            return None;
+        }
+
+        let mut values: Vec<f64> = latest_per_source.into_values().map(|(_, v)| v).collect();
+        values.sort_by(|a, b| a.partial_cmp(b).unwrap());
+        let mid = values.len() / 2;
+        Some(if values.len() % 2 == 1 {
+            values[mid]
+        } else {
+            (values[mid - 1] + values[mid]) / 2.0
+        })
     }
 
-    /// Fetch the most recent value for `key` if present.
-    pub fn get(&self, key: &str) -> Option<i64> {
-        self.values.get(key).copied()
+    /// Computes the time-weighted average price for `key` over `[start, end]`, weighting
+    /// each sample's value by the time until the next sample in the window. Returns `None`
+    /// if fewer than two samples fall inside the window.
+    pub fn twap(&self, key: &str, start: u64, end: u64) -> Option<f64> {
+        let samples = self.samples.get(key)?;
+        let mut window: Vec<&Sample> = samples
+            .iter()
+            .filter(|s| s.timestamp >= start && s.timestamp <= end)
+            .collect();
+        window.sort_by_key(|s| s.timestamp);
+        if window.len() < 2 {
+            return None;
+        }
+
+        let mut weighted_sum = 0.0;
+        let mut total_duration = 0u64;
+        for pair in window.windows(2) {
+            let dt = pair[1].timestamp - pair[0].timestamp;
+            weighted_sum += pair[0].value * dt as f64;
+            total_duration += dt;
+        }
+        if total_duration == 0 {
+            return None;
+        }
+        Some(weighted_sum / total_duration as f64)
     }
 }
 
@@ -36,9 +131,56 @@ mod tests {
 
     #[test]
     fn update_and_get() {
-        let mut oracle = OracleUpdater::new();
-        oracle.update("eth_usd", 3_200);
-        assert_eq!(oracle.get("eth_usd"), Some(3_200));
+        let mut oracle = OracleUpdater::new(1);
+        oracle.update("eth_usd", "binance", 3_200.0, 100);
+        assert_eq!(oracle.get("eth_usd"), Some(3_200.0));
         assert!(oracle.get("btc_usd").is_none());
     }
+
+    #[test]
+    fn median_requires_quorum_of_fresh_sources() {
+        let mut oracle = OracleUpdater::new(3);
+        oracle.update("eth_usd", "binance", 3_000.0, 100);
+        oracle.update("eth_usd", "coinbase", 3_100.0, 100);
+        assert_eq!(oracle.median("eth_usd", 100, 50), None);
+
+        oracle.update("eth_usd", "kraken", 3_200.0, 100);
+        assert_eq!(oracle.median("eth_usd", 100, 50), Some(3_100.0));
+    }
+
+    #[test]
+    fn median_discards_stale_submissions() {
+        let mut oracle = OracleUpdater::new(2);
+        oracle.update("eth_usd", "binance", 3_000.0, 0);
+        oracle.update("eth_usd", "coinbase", 3_200.0, 100);
+        // binance's submission is now 100s stale, older than the 10s ttl.
+        assert_eq!(oracle.median("eth_usd", 100, 10), None);
+    }
+
+    #[test]
+    fn median_uses_each_source_latest_value() {
+        let mut oracle = OracleUpdater::new(2);
+        oracle.update("eth_usd", "binance", 2_900.0, 0);
+        oracle.update("eth_usd", "binance", 3_000.0, 50);
+        oracle.update("eth_usd", "coinbase", 3_200.0, 50);
+        // Both binance prints are fresh; only its latest (3_000) counts, not 2_900.
+        assert_eq!(oracle.median("eth_usd", 50, 100), Some(3_100.0));
+    }
+
+    #[test]
+    fn twap_time_weights_samples_in_window() {
+        let mut oracle = OracleUpdater::new(1);
+        oracle.update("eth_usd", "binance", 100.0, 0);
+        oracle.update("eth_usd", "binance", 200.0, 10);
+        oracle.update("eth_usd", "binance", 100.0, 20);
+        // 100 held for 10s, then 200 held for 10s -> average 150.
+        assert_eq!(oracle.twap("eth_usd", 0, 20), Some(150.0));
+    }
+
+    #[test]
+    fn twap_none_with_fewer_than_two_samples() {
+        let mut oracle = OracleUpdater::new(1);
+        oracle.update("eth_usd", "binance", 100.0, 0);
+        assert_eq!(oracle.twap("eth_usd", 0, 100), None);
+    }
 }
@@ -0,0 +1,68 @@
+//! Pluggable key/value storage, so components don't hard-wire their state to one in-memory
+//! structure. [`InMemoryStorage`] backs tests and the default runtime; a persistent
+//! implementation can later satisfy the same [`Storage`] trait without touching its callers.
+
+use std::collections::HashMap;
+
+/// A minimal keyed storage backend.
+pub trait Storage {
+    /// Returns the value stored under `key`, if any.
+    fn get(&self, key: &[u8]) -> Option<Vec<u8>>;
+    /// Stores `value` under `key`, overwriting any existing value.
+    fn insert(&mut self, key: Vec<u8>, value: Vec<u8>);
+    /// Removes the value stored under `key`, if any.
+    fn remove(&mut self, key: &[u8]);
+}
+
+/// A [`Storage`] backed by an in-memory map.
+#[derive(Default)]
+pub struct InMemoryStorage {
+    entries: HashMap<Vec<u8>, Vec<u8>>,
+}
+
+impl InMemoryStorage {
+    /// Creates an empty store.
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl Storage for InMemoryStorage {
+    fn get(&self, key: &[u8]) -> Option<Vec<u8>> {
+        self.entries.get(key).cloned()
+    }
+
+    fn insert(&mut self, key: Vec<u8>, value: Vec<u8>) {
+        self.entries.insert(key, value);
+    }
+
+    fn remove(&mut self, key: &[u8]) {
+        self.entries.remove(key);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn insert_then_get_round_trips() {
+        let mut store = InMemoryStorage::new();
+        store.insert(b"key".to_vec(), b"value".to_vec());
+        assert_eq!(store.get(b"key"), Some(b"value".to_vec()));
+    }
+
+    #[test]
+    fn missing_key_returns_none() {
+        let store = InMemoryStorage::new();
+        assert_eq!(store.get(b"missing"), None);
+    }
+
+    #[test]
+    fn remove_deletes_the_entry() {
+        let mut store = InMemoryStorage::new();
+        store.insert(b"key".to_vec(), b"value".to_vec());
+        store.remove(b"key");
+        assert_eq!(store.get(b"key"), None);
+    }
+}
@@ -0,0 +1,159 @@
+//! Transaction fee metering and pre-execution validation.
+//!
+//! The VM previously accepted a bare `(opcode, gas)` pair with no notion of transaction size,
+//! price, or affordability. Following the check-before-execute pattern, [`Transaction::check`]
+//! computes the intrinsic + per-byte fee, verifies the sender can afford the transaction's max
+//! spend, and validates the signature, producing a [`Checked`] transaction that caches the fee
+//! so it isn't recomputed when the VM later runs it.
+
+#[path = "keccak.rs"]
+mod keccak;
+
+use keccak::keccak256;
+use std::collections::HashMap;
+
+/// Flat cost charged for including any transaction, regardless of payload size.
+pub const INTRINSIC_COST: u64 = 21_000;
+/// Additional cost charged per byte of `payload`.
+pub const PER_BYTE_COST: u64 = 16;
+
+/// Errors returned by [`Transaction::check`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CheckError {
+    /// `gas_limit * gas_price` is too small to cover the computed fee.
+    InsufficientGasLimit,
+    /// The sender's balance cannot cover the transaction's max spend.
+    InsufficientBalance,
+    /// `signature` does not match the expected digest of `sender` and `payload`.
+    InvalidSignature,
+}
+
+/// An unvalidated transaction.
+#[derive(Debug)]
+pub struct Transaction {
+    pub gas_limit: u64,
+    pub gas_price: u64,
+    pub payload: Vec<u8>,
+    pub signature: [u8; 32],
+    pub sender: String,
+}
+
+impl Transaction {
+    /// Builds a transaction for `sender`, signing it with the expected keccak digest of
+    /// `sender || payload`.
+    pub fn new(gas_limit: u64, gas_price: u64, payload: Vec<u8>, sender: impl Into<String>) -> Self {
+        let sender = sender.into();
+        let signature = Self::expected_signature(&sender, &payload);
+        Self {
+            gas_limit,
+            gas_price,
+            payload,
+            signature,
+            sender,
+        }
+    }
+
+    /// The digest a correctly-signed transaction from `sender` over `payload` must carry.
+    pub fn expected_signature(sender: &str, payload: &[u8]) -> [u8; 32] {
+        let mut buf = Vec::with_capacity(sender.len() + payload.len());
+        buf.extend_from_slice(sender.as_bytes());
+        buf.extend_from_slice(payload);
+        keccak256(&buf)
+    }
+
+    fn fee(&self) -> u64 {
+        INTRINSIC_COST + PER_BYTE_COST * self.payload.len() as u64
+    }
+
+    /// Validates gas affordability, sender balance, and signature, in that order. On success
+    /// returns a [`Checked`] wrapper caching the computed fee.
+    pub fn check(self, balances: &HashMap<String, u64>) -> Result<Checked, CheckError> {
+        let fee = self.fee();
+        let max_spend = self.gas_limit.saturating_mul(self.gas_price);
+        if fee > max_spend {
+            return Err(CheckError::InsufficientGasLimit);
+        }
+        let balance = balances.get(&self.sender).copied().unwrap_or(0);
+        if balance < max_spend {
+            return Err(CheckError::InsufficientBalance);
+        }
+        if self.signature != Self::expected_signature(&self.sender, &self.payload) {
+            return Err(CheckError::InvalidSignature);
+        }
+        Ok(Checked {
+            transaction: self,
+            fee,
+        })
+    }
+}
+
+/// A transaction that has passed [`Transaction::check`], with its fee already computed.
+#[derive(Debug)]
+pub struct Checked {
+    transaction: Transaction,
+    fee: u64,
+}
+
+impl Checked {
+    /// The fee computed during validation.
+    pub fn fee(&self) -> u64 {
+        self.fee
+    }
+
+    /// The validated transaction.
+    pub fn transaction(&self) -> &Transaction {
+        &self.transaction
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn balances() -> HashMap<String, u64> {
+        HashMap::from([("alice".to_string(), 1_000_000)])
+    }
+
+    #[test]
+    fn fee_is_intrinsic_plus_per_byte_cost() {
+        let tx = Transaction::new(100_000, 1, vec![0u8; 10], "alice");
+        let checked = tx.check(&balances()).unwrap();
+        assert_eq!(checked.fee(), INTRINSIC_COST + PER_BYTE_COST * 10);
+    }
+
+    #[test]
+    fn rejects_a_gas_limit_too_small_to_cover_the_fee() {
+        let tx = Transaction::new(1, 1, vec![0u8; 10], "alice");
+        assert_eq!(
+            tx.check(&balances()).unwrap_err(),
+            CheckError::InsufficientGasLimit
+        );
+    }
+
+    #[test]
+    fn rejects_a_sender_who_cannot_afford_the_max_spend() {
+        let tx = Transaction::new(100_000, 1, vec![], "bob");
+        assert_eq!(
+            tx.check(&balances()).unwrap_err(),
+            CheckError::InsufficientBalance
+        );
+    }
+
+    #[test]
+    fn rejects_a_tampered_signature() {
+        let mut tx = Transaction::new(100_000, 1, vec![], "alice");
+        tx.signature = [0u8; 32];
+        assert_eq!(
+            tx.check(&balances()).unwrap_err(),
+            CheckError::InvalidSignature
+        );
+    }
+
+    #[test]
+    fn checked_caches_the_fee_alongside_the_transaction() {
+        let tx = Transaction::new(100_000, 1, vec![1, 2, 3], "alice");
+        let checked = tx.check(&balances()).unwrap();
+        assert_eq!(checked.transaction().sender, "alice");
+        assert_eq!(checked.fee(), INTRINSIC_COST + PER_BYTE_COST * 3);
+    }
+}
@@ -0,0 +1,337 @@
+//! A small stack-based bytecode VM shared by the contract stubs.
+//!
+//! Modelled on the fuel-asm/fuel-vm split: opcodes are a typed [`Instruction`] set executed
+//! under a gas meter, instead of every contract duplicating its own `gas == 0` no-op check.
+//! [`Vm::run`] decodes and executes a full program; [`Vm::execute_single`] decodes and
+//! executes just one instruction, which is what each contract's `execute_opcode` wrapper
+//! drives per call. `Load`/`Store` read and write through a caller-supplied [`Storage`]
+//! handle rather than a VM-private map, so contracts can plug in whatever backend they use
+//! for persistence.
+
+use std::fmt;
+
+#[path = "tx.rs"]
+pub mod tx;
+
+#[path = "storage.rs"]
+pub mod storage;
+
+use storage::Storage;
+
+pub const OP_HALT: u8 = 0x00;
+pub const OP_PUSH: u8 = 0x01;
+pub const OP_POP: u8 = 0x02;
+pub const OP_ADD: u8 = 0x03;
+pub const OP_SUB: u8 = 0x04;
+pub const OP_MUL: u8 = 0x05;
+pub const OP_DIV: u8 = 0x06;
+pub const OP_LOAD: u8 = 0x07;
+pub const OP_STORE: u8 = 0x08;
+
+/// The decoded instruction set. `Push`, `Load` and `Store` carry an operand; the rest act
+/// purely on the operand stack.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Instruction {
+    Halt,
+    Push(u64),
+    Pop,
+    Add,
+    Sub,
+    Mul,
+    Div,
+    Load(u64),
+    Store(u64),
+}
+
+/// Decodes the instruction at `opcode`, consuming `operand` for opcodes that need one.
+pub fn decode(opcode: u8, operand: u64) -> Result<Instruction, VmError> {
+    match opcode {
+        OP_HALT => Ok(Instruction::Halt),
+        OP_PUSH => Ok(Instruction::Push(operand)),
+        OP_POP => Ok(Instruction::Pop),
+        OP_ADD => Ok(Instruction::Add),
+        OP_SUB => Ok(Instruction::Sub),
+        OP_MUL => Ok(Instruction::Mul),
+        OP_DIV => Ok(Instruction::Div),
+        OP_LOAD => Ok(Instruction::Load(operand)),
+        OP_STORE => Ok(Instruction::Store(operand)),
+        other => Err(VmError::UnknownOpcode(other)),
+    }
+}
+
+/// The static gas cost charged for each opcode before it executes.
+pub fn gas_cost(opcode: u8) -> u64 {
+    match opcode {
+        OP_HALT | OP_POP => 1,
+        OP_PUSH => 3,
+        OP_ADD | OP_SUB | OP_MUL | OP_DIV => 5,
+        OP_LOAD | OP_STORE => 10,
+        _ => 0,
+    }
+}
+
+/// Errors raised while decoding or executing an instruction.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum VmError {
+    /// `u8` does not map to a known opcode.
+    UnknownOpcode(u8),
+    /// Charging the opcode's gas cost would make `gas_remaining` go negative.
+    OutOfGas,
+    /// A `Pop`, arithmetic or `Store` opcode ran with too few operands on the stack.
+    StackUnderflow,
+    /// `Div` was executed with a zero divisor.
+    DivisionByZero,
+}
+
+impl fmt::Display for VmError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            VmError::UnknownOpcode(op) => write!(f, "unknown opcode: {op:#04x}"),
+            VmError::OutOfGas => write!(f, "out of gas"),
+            VmError::StackUnderflow => write!(f, "stack underflow"),
+            VmError::DivisionByZero => write!(f, "division by zero"),
+        }
+    }
+}
+
+impl std::error::Error for VmError {}
+
+/// A metered stack machine: an operand stack and a gas meter. `Load`/`Store` persist through
+/// whatever [`Storage`] the caller threads into [`Vm::execute_single`]/[`Vm::run`].
+pub struct Vm {
+    pub stack: Vec<u64>,
+    pub gas_remaining: u64,
+    halted: bool,
+}
+
+impl Vm {
+    /// Creates a VM with `gas_limit` gas and an empty stack.
+    pub fn new(gas_limit: u64) -> Self {
+        Self {
+            stack: Vec::new(),
+            gas_remaining: gas_limit,
+            halted: false,
+        }
+    }
+
+    /// `true` once an explicit `Halt` instruction has executed.
+    pub fn halted(&self) -> bool {
+        self.halted
+    }
+
+    fn charge(&mut self, cost: u64) -> Result<(), VmError> {
+        self.gas_remaining = self.gas_remaining.checked_sub(cost).ok_or(VmError::OutOfGas)?;
+        Ok(())
+    }
+
+    fn pop(&mut self) -> Result<u64, VmError> {
+        self.stack.pop().ok_or(VmError::StackUnderflow)
+    }
+
+    fn apply(&mut self, instruction: Instruction, storage: &mut dyn Storage) -> Result<(), VmError> {
+        match instruction {
+            Instruction::Halt => self.halted = true,
+            Instruction::Push(v) => self.stack.push(v),
+            Instruction::Pop => {
+                self.pop()?;
+            }
+            Instruction::Add => {
+                let b = self.pop()?;
+                let a = self.pop()?;
+                self.stack.push(a.wrapping_add(b));
+            }
+            Instruction::Sub => {
+                let b = self.pop()?;
+                let a = self.pop()?;
+                self.stack.push(a.wrapping_sub(b));
+            }
+            Instruction::Mul => {
+                let b = self.pop()?;
+                let a = self.pop()?;
+                self.stack.push(a.wrapping_mul(b));
+            }
+            Instruction::Div => {
+                let b = self.pop()?;
+                let a = self.pop()?;
+                if b == 0 {
+                    return Err(VmError::DivisionByZero);
+                }
+                self.stack.push(a / b);
+            }
+            Instruction::Load(key) => {
+                let value = storage
+                    .get(&key.to_be_bytes())
+                    .map(|bytes| u64::from_be_bytes(bytes.try_into().unwrap_or([0; 8])))
+                    .unwrap_or(0);
+                self.stack.push(value);
+            }
+            Instruction::Store(key) => {
+                let value = self.pop()?;
+                storage.insert(key.to_be_bytes().to_vec(), value.to_be_bytes().to_vec());
+            }
+        }
+        Ok(())
+    }
+
+    /// Decodes and executes the single instruction `(opcode, operand)`, charging its gas
+    /// cost first so a too-expensive instruction never touches the stack or storage.
+    pub fn execute_single(
+        &mut self,
+        opcode: u8,
+        operand: u64,
+        storage: &mut dyn Storage,
+    ) -> Result<(), VmError> {
+        let instruction = decode(opcode, operand)?;
+        self.charge(gas_cost(opcode))?;
+        self.apply(instruction, storage)
+    }
+
+    /// Runs a full program: a flat sequence of `(opcode, operand)` pairs, stopping on an
+    /// explicit `Halt` or when the program ends.
+    pub fn run(&mut self, program: &[(u8, u64)], storage: &mut dyn Storage) -> Result<(), VmError> {
+        for &(opcode, operand) in program {
+            self.execute_single(opcode, operand, storage)?;
+            if self.halted {
+                break;
+            }
+        }
+        Ok(())
+    }
+
+    /// Runs a validated transaction's payload, decoded as a flat sequence of 9-byte
+    /// `(opcode: u8, operand: u64 big-endian)` instructions. Only [`tx::Checked`] transactions
+    /// may be executed, since their fee and balance have already been validated; gas is
+    /// metered against the transaction's `gas_limit`.
+    pub fn run_checked(
+        &mut self,
+        checked: &tx::Checked,
+        storage: &mut dyn Storage,
+    ) -> Result<(), VmError> {
+        self.gas_remaining = checked.transaction().gas_limit;
+        let program: Vec<(u8, u64)> = checked
+            .transaction()
+            .payload
+            .chunks_exact(9)
+            .map(|chunk| (chunk[0], u64::from_be_bytes(chunk[1..9].try_into().unwrap())))
+            .collect();
+        self.run(&program, storage)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use storage::InMemoryStorage;
+
+    #[test]
+    fn runs_arithmetic_program() {
+        let mut vm = Vm::new(100);
+        let mut storage = InMemoryStorage::new();
+        // (2 + 3) * 4 = 20
+        vm.run(
+            &[
+                (OP_PUSH, 2),
+                (OP_PUSH, 3),
+                (OP_ADD, 0),
+                (OP_PUSH, 4),
+                (OP_MUL, 0),
+            ],
+            &mut storage,
+        )
+        .unwrap();
+        assert_eq!(vm.stack, vec![20]);
+    }
+
+    #[test]
+    fn store_and_load_round_trip() {
+        let mut vm = Vm::new(100);
+        let mut storage = InMemoryStorage::new();
+        vm.run(&[(OP_PUSH, 42), (OP_STORE, 7), (OP_LOAD, 7)], &mut storage)
+            .unwrap();
+        assert_eq!(vm.stack, vec![42]);
+    }
+
+    #[test]
+    fn store_persists_across_separate_vm_runs() {
+        let mut storage = InMemoryStorage::new();
+        Vm::new(100)
+            .run(&[(OP_PUSH, 99), (OP_STORE, 1)], &mut storage)
+            .unwrap();
+        let mut vm = Vm::new(100);
+        vm.run(&[(OP_LOAD, 1)], &mut storage).unwrap();
+        assert_eq!(vm.stack, vec![99]);
+    }
+
+    #[test]
+    fn halt_stops_the_program_early() {
+        let mut vm = Vm::new(100);
+        let mut storage = InMemoryStorage::new();
+        vm.run(&[(OP_PUSH, 1), (OP_HALT, 0), (OP_PUSH, 2)], &mut storage)
+            .unwrap();
+        assert_eq!(vm.stack, vec![1]);
+        assert!(vm.halted());
+    }
+
+    #[test]
+    fn unknown_opcode_is_rejected() {
+        let mut vm = Vm::new(100);
+        let mut storage = InMemoryStorage::new();
+        assert_eq!(
+            vm.execute_single(0xFF, 0, &mut storage),
+            Err(VmError::UnknownOpcode(0xFF))
+        );
+    }
+
+    #[test]
+    fn stack_underflow_is_rejected() {
+        let mut vm = Vm::new(100);
+        let mut storage = InMemoryStorage::new();
+        assert_eq!(
+            vm.execute_single(OP_ADD, 0, &mut storage),
+            Err(VmError::StackUnderflow)
+        );
+    }
+
+    #[test]
+    fn division_by_zero_is_rejected() {
+        let mut vm = Vm::new(100);
+        let mut storage = InMemoryStorage::new();
+        vm.run(&[(OP_PUSH, 1), (OP_PUSH, 0)], &mut storage).unwrap();
+        assert_eq!(
+            vm.execute_single(OP_DIV, 0, &mut storage),
+            Err(VmError::DivisionByZero)
+        );
+    }
+
+    #[test]
+    fn gas_never_goes_negative() {
+        let mut vm = Vm::new(2);
+        let mut storage = InMemoryStorage::new();
+        assert_eq!(
+            vm.execute_single(OP_PUSH, 1, &mut storage),
+            Err(VmError::OutOfGas)
+        );
+    }
+
+    #[test]
+    fn run_checked_decodes_and_executes_the_transaction_payload() {
+        use std::collections::HashMap;
+
+        let mut payload = Vec::new();
+        payload.push(OP_PUSH);
+        payload.extend_from_slice(&7u64.to_be_bytes());
+        payload.push(OP_PUSH);
+        payload.extend_from_slice(&5u64.to_be_bytes());
+        payload.push(OP_ADD);
+        payload.extend_from_slice(&0u64.to_be_bytes());
+
+        let tx = tx::Transaction::new(1_000_000, 1, payload, "alice");
+        let balances = HashMap::from([("alice".to_string(), 1_000_000)]);
+        let checked = tx.check(&balances).unwrap();
+
+        let mut vm = Vm::new(0);
+        let mut storage = InMemoryStorage::new();
+        vm.run_checked(&checked, &mut storage).unwrap();
+        assert_eq!(vm.stack, vec![12]);
+    }
+}